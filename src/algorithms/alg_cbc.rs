@@ -1,77 +1,104 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
 use super::Kuznechik;
+use super::gamma::Gamma;
+use super::cipher::{BlockCipher, encrypt_slice, BATCH_LEN, with_scratch};
+use super::stream::StreamMode;
+#[cfg(feature = "alloc")]
+use super::stream::{encrypt_all, decrypt_all};
 use crate::KeyStore;
-use crate::types::{Block128, mut_cast_unchecked};
-use crate::transforms::{addition_block128_2, sum_mod_2, addition_rev_block_2, encrypt_block, decrypt_block};
+use crate::transforms::sum_mod_2;
+use crate::error::KuznechikError;
 
-pub struct AlgCbc<'k> {
-    kuz: &'k KeyStore,
-    gamma: Vec<u8>
+pub struct AlgCbc<'k, C: BlockCipher = KeyStore> {
+    cipher: &'k C,
+    gamma: Gamma
 }
 
-impl<'k> Kuznechik<'k> for AlgCbc<'k> {
-    fn new(kuz: &'k KeyStore) -> Self {
+impl<'k, C: BlockCipher> Kuznechik<'k, C> for AlgCbc<'k, C> {
+    fn new(cipher: &'k C) -> Self {
         AlgCbc {
-            kuz, gamma: Vec::new()
+            cipher, gamma: Gamma::default()
         }
     }
 
+    #[cfg(feature = "alloc")]
     fn set_gamma(&mut self, gamma: Vec<u8>) {
-        self.gamma = gamma
+        self.gamma.replace(gamma)
     }
 
-    fn encrypt(&mut self, mut data: Vec<u8>) -> Vec<u8> {
-        if self.gamma.len() < 16 {
-            panic!("Gamma length is less than 16 bytes");
-        }
-
-        addition_block128_2(&mut data);
+    #[cfg(feature = "alloc")]
+    fn try_encrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        encrypt_all(self, data)
+    }
 
-        let count_blocks = data.len() / 16;
-        for i in 0..count_blocks {
-            let data_block: &mut Block128 = mut_cast_unchecked(&mut data[16*i..16*(i+1)]);
-            sum_mod_2(data_block, &self.gamma[..16]);
+    #[cfg(feature = "alloc")]
+    fn try_decrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        decrypt_all(self, data)
+    }
+}
 
-            encrypt_block(data_block, &self.kuz.keys);
-            self.update_gamma(data_block);
-        }
+impl<'k, C: BlockCipher> StreamMode for AlgCbc<'k, C> {
+    fn segment_len(&self) -> usize {
+        C::BLOCK_SIZE
+    }
 
-        data
+    fn is_padded(&self) -> bool {
+        true
     }
 
-    fn decrypt(&mut self, mut data: Vec<u8>) -> Vec<u8> {
-        if self.gamma.len() < 16 {
-            panic!("Gamma length is less than 16 bytes");
+    /// Gamma is a register of z blocks, z >= 1
+    fn check_gamma(&self) -> Result<(), KuznechikError> {
+        let n = C::BLOCK_SIZE;
+        if self.gamma.len() < n || !self.gamma.len().is_multiple_of(n) {
+            return Err(KuznechikError::InvalidGammaLength);
         }
+        Ok(())
+    }
+
+    fn try_set_gamma(&mut self, gamma: &[u8]) -> Result<(), KuznechikError> {
+        self.gamma.set(gamma)
+    }
 
-        let count_blocks = data.len() / 16;
+    fn encrypt_segments(&mut self, data: &mut [u8]) {
+        let n = C::BLOCK_SIZE;
+        let count_blocks = data.len() / n;
         for i in 0..count_blocks {
-            let data_block: &mut Block128 = mut_cast_unchecked(&mut data[16*i..16*(i+1)]);
-            let data_block_c = data_block.clone();
+            let data_block = &mut data[n*i..n*(i+1)];
+            sum_mod_2(data_block, &self.gamma[..n]);
 
-            decrypt_block(data_block, &self.kuz.keys);
-            sum_mod_2(data_block, &self.gamma[..16]);
-            self.update_gamma(&data_block_c);
+            encrypt_slice(self.cipher, data_block);
+            self.update_gamma(data_block);
         }
+    }
 
-        addition_rev_block_2(&mut data);
-        data
+    /// Блоки шифртекста известны заранее, поэтому расшифровываются пачкой, а затем складываются с предыдущими
+    fn decrypt_segments(&mut self, data: &mut [u8]) {
+        let n = C::BLOCK_SIZE;
+        let len = data.len() / n * n;
+
+        with_scratch(BATCH_LEN.min(len), |cipher_text| {
+            for chunk in data[..len].chunks_mut(BATCH_LEN) {
+                let cipher_text = &mut cipher_text[..chunk.len()];
+                cipher_text.copy_from_slice(chunk);
+                self.cipher.decrypt_blocks(chunk);
+
+                for (data_block, block_c) in chunk.chunks_exact_mut(n).zip(cipher_text.chunks_exact(n)) {
+                    sum_mod_2(data_block, &self.gamma[..n]);
+                    self.update_gamma(block_c);
+                }
+            }
+        });
     }
 }
 
-impl<'k> AlgCbc<'k> {
-    fn update_gamma(&mut self, data: &Block128) {
+impl<'k, C: BlockCipher> AlgCbc<'k, C> {
+    fn update_gamma(&mut self, data: &[u8]) {
+        let n = C::BLOCK_SIZE;
         let len = self.gamma.len();
-        assert!(len >= 16, "Gamma length is less than 16 bytes");
-
-        let l = len-16;
-        for i in 0..l {
-            self.gamma[i] = self.gamma[l+i];
-        }
-        for i in 0..16 {
-            self.gamma[l+i] = data[i];
-        }
 
-        // self.gamma[..len-16].copy_from_slice(&self.gamma[len-16..]);
-        // self.gamma[len-16..].copy_from_slice(&data[..]);
+        self.gamma.copy_within(n.., 0);
+        self.gamma[len-n..].copy_from_slice(data);
     }
 }
@@ -1,81 +1,135 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
 use super::Kuznechik;
+use super::gamma::Gamma;
+use super::cipher::{BlockCipher, BATCH_LEN, with_scratch};
+use super::stream::StreamMode;
+#[cfg(feature = "alloc")]
+use super::stream::{encrypt_all, decrypt_all};
 use crate::KeyStore;
-use crate::types::{Block128, mut_cast_unchecked};
-use crate::transforms::{sum_mod_2, addition_block_s_2, addition_rev_block_2, encrypt_block};
-use std::convert::TryInto;
+use crate::transforms::sum_mod_2;
+use crate::error::KuznechikError;
 
-pub struct AlgCfb<'k> {
-    kuz: &'k KeyStore,
-    gamma: Vec<u8>,
+pub struct AlgCfb<'k, C: BlockCipher = KeyStore> {
+    cipher: &'k C,
+    gamma: Gamma,
     s: usize
 }
 
-impl<'k> Kuznechik<'k> for AlgCfb<'k> {
-    fn new(kuz: &'k KeyStore) -> Self {
-        AlgCfb { kuz, gamma: vec![], s: 16 }
+impl<'k, C: BlockCipher> Kuznechik<'k, C> for AlgCfb<'k, C> {
+    fn new(cipher: &'k C) -> Self {
+        AlgCfb { cipher, gamma: Gamma::default(), s: C::BLOCK_SIZE }
     }
 
+    #[cfg(feature = "alloc")]
     fn set_gamma(&mut self, gamma: Vec<u8>) {
-        self.gamma = gamma
+        self.gamma.replace(gamma)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn try_encrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        encrypt_all(self, data)
     }
 
-    fn encrypt(&mut self, mut data: Vec<u8>) -> Vec<u8> {
-        if self.gamma.len() < 16 {
-            panic!("Gamma length is less than 16 bytes");
+    #[cfg(feature = "alloc")]
+    fn try_decrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        decrypt_all(self, data)
+    }
+}
+
+impl<'k, C: BlockCipher> StreamMode for AlgCfb<'k, C> {
+    fn segment_len(&self) -> usize {
+        self.s
+    }
+
+    fn is_padded(&self) -> bool {
+        true
+    }
+
+    fn check_gamma(&self) -> Result<(), KuznechikError> {
+        if self.gamma.len() < C::BLOCK_SIZE {
+            return Err(KuznechikError::InvalidGammaLength);
         }
+        Ok(())
+    }
 
-        addition_block_s_2(&mut data, self.s);
+    fn try_set_gamma(&mut self, gamma: &[u8]) -> Result<(), KuznechikError> {
+        self.gamma.set(gamma)
+    }
 
+    fn encrypt_segments(&mut self, data: &mut [u8]) {
         let count_blocks = data.len() / self.s;
         for i in 0..count_blocks {
-            let mut block : Block128 = self.gamma[..16].try_into().unwrap();
-            encrypt_block(&mut block, &self.kuz.keys);
-
-            let data_block: &mut Block128 = mut_cast_unchecked(&mut data[self.s*i..self.s*(i+1)]);
+            let block = self.gamma_block();
+            let data_block = &mut data[self.s*i..self.s*(i+1)];
 
             // Ts transform
-            sum_mod_2(data_block, &block);
+            sum_mod_2(data_block, block.as_ref());
             self.update_gamma(data_block);
         }
+    }
 
-        data
+    /// Регистр - скользящее окно по IV || C, поэтому входы шифра для всех сегментов известны заранее:
+    /// для i-го сегмента это n байт, начиная с i·s. Они зашифровываются пачкой.
+    fn decrypt_segments(&mut self, data: &mut [u8]) {
+        let (n, s) = (C::BLOCK_SIZE, self.s);
+        let len = data.len() / s * s;
+        let count = BATCH_LEN / n;
+
+        with_scratch(count.min(len / s) * n, |stream| {
+            for chunk in data[..len].chunks_mut(count * s) {
+                let stream = &mut stream[..chunk.len() / s * n];
+                for (i, block) in stream.chunks_exact_mut(n).enumerate() {
+                    self.register_bytes(chunk, s*i, block);
+                }
+                self.shift_gamma(chunk);
+                self.cipher.encrypt_blocks(stream);
+
+                // Ts transform
+                for (data_block, block) in chunk.chunks_exact_mut(s).zip(stream.chunks_exact(n)) {
+                    sum_mod_2(data_block, block);
+                }
+            }
+        });
     }
+}
 
-    fn decrypt(&mut self, mut data: Vec<u8>) -> Vec<u8> {
-        if self.gamma.len() < 16 {
-            panic!("Gamma length is less than 16 bytes");
-        }
+impl<'k, C: BlockCipher> AlgCfb<'k, C> {
+    /// E(MSB_n(R)) - старшие n бит регистра, зашифрованные блочным шифром
+    fn gamma_block(&self) -> C::Block {
+        let mut block = C::Block::default();
+        block.as_mut().copy_from_slice(&self.gamma[..C::BLOCK_SIZE]);
+        self.cipher.encrypt_block(&mut block);
+        block
+    }
 
-        let count_blocks = data.len() / self.s;
-        for i in 0..count_blocks {
-            let mut block : Block128 = self.gamma[..16].try_into().unwrap();
-            encrypt_block(&mut block, &self.kuz.keys);
+    /// Байты последовательности R || C, начиная с `offset`, в `out`
+    fn register_bytes(&self, cipher_text: &[u8], offset: usize, out: &mut [u8]) {
+        let m = self.gamma.len();
+        let from_gamma = m.saturating_sub(offset).min(out.len());
+        out[..from_gamma].copy_from_slice(&self.gamma[offset.min(m)..offset.min(m) + from_gamma]);
 
-            let data_block: &mut Block128 = mut_cast_unchecked(&mut data[self.s*i..self.s*(i+1)]);
+        let start = (offset + from_gamma).saturating_sub(m);
+        let rest = out.len() - from_gamma;
+        out[from_gamma..].copy_from_slice(&cipher_text[start..start + rest]);
+    }
 
-            self.update_gamma(&data_block[..]);
-            // Ts transform
-            sum_mod_2(data_block, &block);
+    /// Сдвигает в регистр весь шифртекст пачки
+    fn shift_gamma(&mut self, cipher_text: &[u8]) {
+        let (m, len) = (self.gamma.len(), cipher_text.len());
+        if len >= m {
+            self.gamma.copy_from_slice(&cipher_text[len - m..]);
+        } else {
+            self.gamma.copy_within(len.., 0);
+            self.gamma[m - len..].copy_from_slice(cipher_text);
         }
-
-        addition_rev_block_2(&mut data);
-
-        data
     }
-}
 
-impl<'k> AlgCfb<'k> {
     fn update_gamma(&mut self, data: &[u8]) {
         let len = self.gamma.len();
-        assert!(len >= 16, "Gamma length is less than 16 bytes");
-        assert!(data.len() >= self.s, "Data length is less than s bytes");
 
-        let l = len - self.s;
-        for i in 0..l {
-            self.gamma[i] = self.gamma[i + self.s];
-        }
-        for i in 0..self.s {
-            self.gamma[l+i] = data[i];
-        }
+        self.gamma.copy_within(self.s.., 0);
+        self.gamma[len - self.s..].copy_from_slice(&data[..self.s]);
     }
 }
@@ -1,53 +1,89 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
 use super::Kuznechik;
+use super::gamma::Gamma;
+use super::cipher::{BlockCipher, BATCH_LEN, with_scratch};
+use super::stream::StreamMode;
+#[cfg(feature = "alloc")]
+use super::stream::{encrypt_all, decrypt_all};
 use crate::KeyStore;
-use crate::types::Block128;
-use crate::transforms::{sum_mod_2, encrypt_block};
-use std::convert::TryInto;
+use crate::transforms::sum_mod_2;
+use crate::error::KuznechikError;
 
-pub struct AlgCtr<'k> {
-    kuz: &'k KeyStore,
-    gamma: Vec<u8>
+pub struct AlgCtr<'k, C: BlockCipher = KeyStore> {
+    cipher: &'k C,
+    gamma: Gamma
 }
 
-impl<'k> Kuznechik<'k> for AlgCtr<'k> {
-    fn new(kuz: &'k KeyStore) -> Self {
+impl<'k, C: BlockCipher> Kuznechik<'k, C> for AlgCtr<'k, C> {
+    fn new(cipher: &'k C) -> Self {
         AlgCtr {
-            kuz, gamma: vec![]
+            cipher, gamma: Gamma::default()
         }
     }
 
+    #[cfg(feature = "alloc")]
     fn set_gamma(&mut self, gamma: Vec<u8>) {
-        self.gamma = gamma
+        self.gamma.replace(gamma)
     }
 
-    fn encrypt(&mut self, mut data: Vec<u8>) -> Vec<u8> {
-        let count_blocks = data.len() / 16;  // block128 len 16
+    #[cfg(feature = "alloc")]
+    fn try_encrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        encrypt_all(self, data)
+    }
 
-        for i in 0..count_blocks {
-            let mut block : Block128 = self.gamma[..16].try_into().unwrap();
-            encrypt_block(&mut block, &self.kuz.keys,);
-            sum_mod_2(&mut data[16*i..16*(i+1)], &block);
-            add_ctr(&mut self.gamma);
-        }
+    #[cfg(feature = "alloc")]
+    fn try_decrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        decrypt_all(self, data)
+    }
+}
+
+impl<'k, C: BlockCipher> StreamMode for AlgCtr<'k, C> {
+    fn segment_len(&self) -> usize {
+        C::BLOCK_SIZE
+    }
 
-        let q_len = data.len() - count_blocks * 16;
-        if q_len > 0 {
-            let mut block : Block128 = self.gamma[..16].try_into().unwrap();
-            encrypt_block(&mut block, &self.kuz.keys);
-            sum_mod_2(&mut data[16*count_blocks..], &block[..]);
-            add_ctr(&mut self.gamma);
+    fn is_padded(&self) -> bool {
+        false
+    }
+
+    fn check_gamma(&self) -> Result<(), KuznechikError> {
+        if self.gamma.len() != C::BLOCK_SIZE {
+            return Err(KuznechikError::InvalidGammaLength);
         }
-        data
+        Ok(())
+    }
+
+    fn try_set_gamma(&mut self, gamma: &[u8]) -> Result<(), KuznechikError> {
+        self.gamma.set(gamma)
+    }
+
+    /// Гамма вырабатывается пачками по `BATCH_LEN` байт, чтобы шифр мог обрабатывать блоки одновременно;
+    /// счётчики пачки заполняются последовательно, так что каждая часть начинается со своего смещения счётчика
+    fn encrypt_segments(&mut self, data: &mut [u8]) {
+        let n = C::BLOCK_SIZE;
+        with_scratch(BATCH_LEN.min(data.len().div_ceil(n) * n), |stream| {
+            for chunk in data.chunks_mut(BATCH_LEN) {
+                let stream = &mut stream[..chunk.len().div_ceil(n) * n];
+                for block in stream.chunks_exact_mut(n) {
+                    block.copy_from_slice(&self.gamma);
+                    add_ctr(&mut self.gamma);
+                }
+                self.cipher.encrypt_blocks(stream);
+                sum_mod_2(chunk, stream);
+            }
+        });
     }
 
-    fn decrypt(&mut self, data: Vec<u8>) -> Vec<u8> {
-        self.encrypt(data)
+    fn decrypt_segments(&mut self, data: &mut [u8]) {
+        self.encrypt_segments(data)
     }
 }
 
-fn add_ctr(ctr: &mut Vec<u8>) {
+pub(crate) fn add_ctr(ctr: &mut [u8]) {
     for i in (0..ctr.len()).rev() {
-        ctr[i] += 1;
+        ctr[i] = ctr[i].wrapping_add(1);
         if ctr[i] != 0 {
             break;
         }
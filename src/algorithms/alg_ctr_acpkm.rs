@@ -0,0 +1,153 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use super::Kuznechik;
+use super::gamma::Gamma;
+use super::cipher::{MasterKeyInit, encrypt_slice};
+use super::alg_ctr::add_ctr;
+use super::stream::StreamMode;
+#[cfg(feature = "alloc")]
+use super::stream::{encrypt_all, decrypt_all};
+use crate::KeyStore;
+use zeroize::Zeroize;
+use crate::types::Block256;
+use crate::transforms::sum_mod_2;
+use crate::error::KuznechikError;
+
+/// Длина секции по умолчанию в байтах / Default section size in bytes
+const DEFAULT_SECTION_SIZE: usize = 4096;
+
+///
+/// Режим гаммирования с внутренней сменой ключа CTR-ACPKM (Р 1323565.1.017-2018, RFC 8645)
+/// Counter mode with ACPKM internal re-keying
+///
+/// Гамма - синхропосылка IV длины n/2, счётчик CTR_1 = IV || 0^(n/2) увеличивается в младшей половине блока.
+/// После каждой секции из N байт ключ заменяется на ACPKM(K), счётчик при этом не сбрасывается.
+/// `set_gamma` starts a new message: the counter and the key are reset to their initial values.
+///
+pub struct AlgCtrAcpkm<'k, C: MasterKeyInit = KeyStore> {
+    cipher: &'k C,
+    section_key: Option<C>,
+    gamma: Gamma,
+    section_size: usize,
+    section_pos: usize,
+}
+
+impl<'k, C: MasterKeyInit> Kuznechik<'k, C> for AlgCtrAcpkm<'k, C> {
+    fn new(cipher: &'k C) -> Self {
+        AlgCtrAcpkm {
+            cipher,
+            section_key: None,
+            gamma: Gamma::default(),
+            section_size: DEFAULT_SECTION_SIZE,
+            section_pos: 0,
+        }
+    }
+
+    /// IV длины n/2 / IV of half a block
+    #[cfg(feature = "alloc")]
+    fn set_gamma(&mut self, mut gamma: Vec<u8>) {
+        // С `alloc` длина гаммы не ограничена, ошибки нет
+        let _ = self.try_set_gamma(&gamma);
+        gamma.zeroize();
+    }
+
+    #[cfg(feature = "alloc")]
+    fn try_encrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        encrypt_all(self, data)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn try_decrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        decrypt_all(self, data)
+    }
+}
+
+impl<'k, C: MasterKeyInit> AlgCtrAcpkm<'k, C> {
+    pub fn section_size(mut self, section_size: usize) -> Result<Self, KuznechikError> {
+        self.set_section_size(section_size)?;
+        Ok(self)
+    }
+
+    /// Длина секции N в байтах, кратная длине блока / Section size N in bytes, a multiple of the block length
+    pub fn set_section_size(&mut self, section_size: usize) -> Result<(), KuznechikError> {
+        if section_size == 0 || !section_size.is_multiple_of(C::BLOCK_SIZE) {
+            return Err(KuznechikError::InvalidSectionSize);
+        }
+        self.section_size = section_size;
+        Ok(())
+    }
+
+    fn current_key(&self) -> &C {
+        self.section_key.as_ref().unwrap_or(self.cipher)
+    }
+
+    /// Очередной блок гаммы; на границе секции ключ заменяется на ACPKM(K)
+    fn next_block(&mut self) -> C::Block {
+        let n = C::BLOCK_SIZE;
+
+        if self.section_pos == self.section_size {
+            self.section_key = Some(acpkm(self.current_key()));
+            self.section_pos = 0;
+        }
+
+        let mut block = C::Block::default();
+        block.as_mut().copy_from_slice(&self.gamma);
+        self.current_key().encrypt_block(&mut block);
+
+        add_ctr(&mut self.gamma[n / 2..]);
+        self.section_pos += n;
+        block
+    }
+}
+
+impl<'k, C: MasterKeyInit> StreamMode for AlgCtrAcpkm<'k, C> {
+    fn segment_len(&self) -> usize {
+        C::BLOCK_SIZE
+    }
+
+    fn is_padded(&self) -> bool {
+        false
+    }
+
+    fn check_gamma(&self) -> Result<(), KuznechikError> {
+        if self.gamma.len() != C::BLOCK_SIZE {
+            return Err(KuznechikError::InvalidGammaLength);
+        }
+        Ok(())
+    }
+
+    /// Новое сообщение: счётчик IV || 0^(n/2) и исходный ключ
+    fn try_set_gamma(&mut self, gamma: &[u8]) -> Result<(), KuznechikError> {
+        self.gamma.set_with_zeros(gamma, C::BLOCK_SIZE / 2)?;
+        self.section_key = None;
+        self.section_pos = 0;
+        Ok(())
+    }
+
+    fn encrypt_segments(&mut self, data: &mut [u8]) {
+        for chunk in data.chunks_mut(C::BLOCK_SIZE) {
+            let block = self.next_block();
+            sum_mod_2(chunk, block.as_ref());
+        }
+    }
+
+    fn decrypt_segments(&mut self, data: &mut [u8]) {
+        self.encrypt_segments(data)
+    }
+}
+
+/// Преобразование ACPKM: K' = MSB_k(E_K(D_1) || ... || E_K(D_{k/n})), D = 0x80 0x81 ... 0x9f
+pub(crate) fn acpkm<C: MasterKeyInit>(cipher: &C) -> C {
+    let mut key: Block256 = [0u8; 32];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = 0x80 + i as u8;
+    }
+
+    for chunk in key.chunks_mut(C::BLOCK_SIZE) {
+        encrypt_slice(cipher, chunk);
+    }
+    let next = cipher.rekeyed(key);
+    key.zeroize();
+    next
+}
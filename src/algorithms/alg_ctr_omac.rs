@@ -0,0 +1,139 @@
+use alloc::vec::Vec;
+use zeroize::Zeroize;
+
+use super::Kuznechik;
+use super::alg_ctr::AlgCtr;
+use super::alg_mac::AlgMac;
+use super::cipher::MasterKeyInit;
+use super::stream::StreamMode;
+use crate::KeyStore;
+use crate::types::Block256;
+use crate::error::KuznechikError;
+
+/// Метки ключей шифрования и имитовставки / Encryption and MAC key labels
+const ENC_KEY_LABEL: u8 = 0x01;
+const MAC_KEY_LABEL: u8 = 0x02;
+
+///
+/// Аутентифицированное шифрование CTR + OMAC (шифрование, затем имитовставка) / Encrypt-then-MAC from CTR and OMAC
+///
+/// Из ключа K вырабатываются независимые ключи шифрования и имитовставки:
+/// K_label = E_K(label || 0 ... 0 || 1) || E_K(label || 0 ... 0 || 2) || ... (32 байта).
+/// Данные гаммируются в режиме CTR (счётчик IV || 0^(n/2)), имитовставка OMAC вычисляется по шифртексту:
+/// OMAC(IV || A || C || len(A) || len(C)), длины - 64-битные числа байт в big-endian.
+/// `open` checks the tag in constant time before decrypting; the n/2 IV must never repeat under one key.
+///
+/// ```
+/// use kuznechik::{KeyStore, CtrOmac};
+///
+/// let kuz = KeyStore::with_password("Kuznechik");
+/// let iv = [0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xce, 0xf0];
+///
+/// let aead = CtrOmac::new(&kuz);
+/// let sealed = aead.seal(&iv, b"header", Vec::from("Hello, World!")).unwrap();
+///
+/// assert_eq!(aead.open(&iv, b"header", sealed.clone()).unwrap(), b"Hello, World!");
+/// assert!(aead.open(&iv, b"other header", sealed).is_err());
+/// ```
+///
+pub struct CtrOmac<C: MasterKeyInit = KeyStore> {
+    enc_key: C,
+    mac_key: C,
+    tag_len: usize,
+}
+
+impl<C: MasterKeyInit> CtrOmac<C> {
+    /// Имитовставка по умолчанию - полный блок / The tag is a full block by default
+    pub fn new(cipher: &C) -> Self {
+        CtrOmac {
+            enc_key: subkey(cipher, ENC_KEY_LABEL),
+            mac_key: subkey(cipher, MAC_KEY_LABEL),
+            tag_len: C::BLOCK_SIZE,
+        }
+    }
+
+    pub fn tag_len(mut self, tag_len: usize) -> Result<Self, KuznechikError> {
+        self.set_tag_len(tag_len)?;
+        Ok(self)
+    }
+
+    /// Длина имитовставки в байтах, 4 <= S <= n / Tag length in bytes, from 32 bits up to the block length
+    pub fn set_tag_len(&mut self, tag_len: usize) -> Result<(), KuznechikError> {
+        if !(4..=C::BLOCK_SIZE).contains(&tag_len) {
+            return Err(KuznechikError::InvalidTagLength);
+        }
+        self.tag_len = tag_len;
+        Ok(())
+    }
+
+    /// Зашифрование и выработка имитовставки / Encrypts `data` and appends the tag
+    pub fn seal(&self, iv: &[u8], associated_data: &[u8], mut data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        let tag = self.encrypt_in_place_detached(iv, associated_data, &mut data)?;
+        data.extend_from_slice(&tag);
+        Ok(data)
+    }
+
+    /// Проверка имитовставки и расшифрование / Verifies the tag and decrypts
+    pub fn open(&self, iv: &[u8], associated_data: &[u8], mut data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        if data.len() < self.tag_len {
+            return Err(KuznechikError::AuthenticationFailed);
+        }
+        let tag = data.split_off(data.len() - self.tag_len);
+        self.decrypt_in_place_detached(iv, associated_data, &mut data, &tag)?;
+        Ok(data)
+    }
+
+    /// Зашифрование на месте, имитовставка возвращается отдельно / Encrypts in place and returns the tag
+    pub fn encrypt_in_place_detached(&self, iv: &[u8], associated_data: &[u8], data: &mut [u8]) -> Result<Vec<u8>, KuznechikError> {
+        self.ctr(iv)?.encrypt_in_place(data)?;
+        Ok(self.mac(iv, associated_data, data)?.finalize())
+    }
+
+    /// Расшифрование на месте после проверки имитовставки; при несовпадении `data` не изменяется
+    /// Decrypts in place after the tag check; `data` is left untouched on a mismatch
+    pub fn decrypt_in_place_detached(&self, iv: &[u8], associated_data: &[u8], data: &mut [u8], tag: &[u8]) -> Result<(), KuznechikError> {
+        let mut ctr = self.ctr(iv)?;
+        self.mac(iv, associated_data, data)?.verify(tag)?;
+        ctr.decrypt_in_place(data)
+    }
+
+    /// CTR со счётчиком IV || 0^(n/2) / CTR with the counter IV || 0^(n/2)
+    fn ctr(&self, iv: &[u8]) -> Result<AlgCtr<'_, C>, KuznechikError> {
+        let n = C::BLOCK_SIZE;
+        if iv.len() != n / 2 {
+            return Err(KuznechikError::InvalidGammaLength);
+        }
+
+        let mut counter = C::Block::default();
+        counter.as_mut()[..n / 2].copy_from_slice(iv);
+        AlgCtr::new(&self.enc_key).try_gamma(counter.as_ref())
+    }
+
+    /// OMAC(IV || A || C || len(A) || len(C))
+    fn mac(&self, iv: &[u8], associated_data: &[u8], cipher_text: &[u8]) -> Result<AlgMac<'_, C>, KuznechikError> {
+        let mut mac = AlgMac::new(&self.mac_key).tag_len(self.tag_len)?;
+        mac.update(iv);
+        mac.update(associated_data);
+        mac.update(cipher_text);
+        mac.update(&(associated_data.len() as u64).to_be_bytes());
+        mac.update(&(cipher_text.len() as u64).to_be_bytes());
+        Ok(mac)
+    }
+}
+
+/// Ключ K_label из 32 байт E_K(label || 0 ... 0 || i) с настройками исходного ключа
+fn subkey<C: MasterKeyInit>(cipher: &C, label: u8) -> C {
+    let mut master_key: Block256 = [0u8; 32];
+    for (i, chunk) in master_key.chunks_mut(C::BLOCK_SIZE).enumerate() {
+        let mut block = C::Block::default();
+        block.as_mut()[0] = label;
+        block.as_mut()[C::BLOCK_SIZE - 1] = i as u8 + 1;
+        cipher.encrypt_block(&mut block);
+        chunk.copy_from_slice(block.as_ref());
+        block.as_mut().zeroize();
+    }
+
+    let key = cipher.rekeyed(master_key);
+    master_key.zeroize();
+    key
+}
@@ -1,38 +1,61 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
 use super::Kuznechik;
-use crate::types::mut_cast_unchecked;
-use crate::transforms::{addition_block128_2, addition_rev_block_2, encrypt_block, decrypt_block};
+use super::cipher::BlockCipher;
+use super::stream::StreamMode;
+#[cfg(feature = "alloc")]
+use super::stream::{encrypt_all, decrypt_all};
 use crate::key_store::KeyStore;
+use crate::error::KuznechikError;
 
-pub struct AlgEcb<'k> {
-    kuz: &'k KeyStore
+pub struct AlgEcb<'k, C: BlockCipher = KeyStore> {
+    cipher: &'k C
 }
 
-impl<'k> Kuznechik<'k> for AlgEcb<'k> {
-    fn new(kuz: &'k KeyStore) -> Self {
-        AlgEcb { kuz }
+impl<'k, C: BlockCipher> Kuznechik<'k, C> for AlgEcb<'k, C> {
+    fn new(cipher: &'k C) -> Self {
+        AlgEcb { cipher }
     }
 
+    #[cfg(feature = "alloc")]
     fn set_gamma(&mut self, _gamma: Vec<u8>) { }
 
-    fn encrypt(&mut self, mut data: Vec<u8>) -> Vec<u8> {
-        addition_block128_2(&mut data);
+    #[cfg(feature = "alloc")]
+    fn try_encrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        encrypt_all(self, data)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn try_decrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        decrypt_all(self, data)
+    }
+}
+
+impl<'k, C: BlockCipher> StreamMode for AlgEcb<'k, C> {
+    fn segment_len(&self) -> usize {
+        C::BLOCK_SIZE
+    }
 
-        let count_blocks = data.len() / 16;
+    fn is_padded(&self) -> bool {
+        true
+    }
 
-        for i in 0..count_blocks {
-            encrypt_block(mut_cast_unchecked(&mut data[i*16 .. (i+1)*16]), &self.kuz.keys);
-        }
-        data
+    fn check_gamma(&self) -> Result<(), KuznechikError> {
+        Ok(())
     }
 
-    fn decrypt(&mut self, mut data: Vec<u8>) -> Vec<u8> {
-        let count_blocks = data.len() / 16;
+    fn try_set_gamma(&mut self, _gamma: &[u8]) -> Result<(), KuznechikError> {
+        Ok(())
+    }
 
-        for i in 0..count_blocks {
-            decrypt_block(mut_cast_unchecked(&mut data[i*16 .. (i+1)*16]), &self.kuz.keys);
-        }
+    fn encrypt_segments(&mut self, data: &mut [u8]) {
+        let len = data.len() / C::BLOCK_SIZE * C::BLOCK_SIZE;
+        self.cipher.encrypt_blocks(&mut data[..len]);
+    }
 
-        addition_rev_block_2(&mut data);
-        data
+    fn decrypt_segments(&mut self, data: &mut [u8]) {
+        let len = data.len() / C::BLOCK_SIZE * C::BLOCK_SIZE;
+        self.cipher.decrypt_blocks(&mut data[..len]);
     }
 }
@@ -1,23 +1,54 @@
+use alloc::vec::Vec;
+
 use super::Kuznechik;
+use super::cipher::BlockCipher;
 use crate::KeyStore;
-use crate::types::Block128;
-use crate::transforms::{sum_mod_2, encrypt_block};
-use std::convert::TryInto;
+use zeroize::Zeroize;
+use crate::transforms::{sum_mod_2, ct_eq};
+use crate::error::KuznechikError;
 
-pub struct AlgMac<'k> {
-    kuz: &'k KeyStore,
+///
+/// Режим выработки имитовставки MAC (ГОСТ Р 34.13-2015, п. 5.6) / Message Authentication Code
+///
+/// Сообщение можно подавать частями через `update`, длинные файлы не нужно держать в памяти.
+/// `encrypt` is `update` followed by `finalize`; after `finalize` or `verify` a new message starts.
+///
+/// ```
+/// use kuznechik::{KeyStore, Kuznechik, AlgMac};
+///
+/// let kuz = KeyStore::with_password("Kuznechik");
+///
+/// let mut mac = AlgMac::new(&kuz).tag_len(16).unwrap();
+/// mac.update(b"Hello, ");
+/// mac.update(b"World!");
+/// let tag = mac.finalize();
+///
+/// assert_eq!(tag, AlgMac::new(&kuz).tag_len(16).unwrap().encrypt(b"Hello, World!".to_vec()));
+///
+/// mac.update(b"Hello, World!");
+/// assert!(mac.verify(&tag).is_ok());
+/// ```
+///
+pub struct AlgMac<'k, C: BlockCipher = KeyStore> {
+    cipher: &'k C,
     s: usize,
-    k1: Block128,
-    k2: Block128
+    k1: C::Block,
+    k2: C::Block,
+    state: C::Block,
+    last: C::Block,
+    last_len: usize,
 }
 
-impl<'k> Kuznechik<'k> for AlgMac<'k> {
-    fn new(kuz: &'k KeyStore) -> Self {
+impl<'k, C: BlockCipher> Kuznechik<'k, C> for AlgMac<'k, C> {
+    fn new(cipher: &'k C) -> Self {
         let mut a = AlgMac {
-            kuz,
-            s: 8,
-            k1: [0u8; 16],
-            k2: [0u8; 16]
+            cipher,
+            s: C::BLOCK_SIZE / 2,
+            k1: C::Block::default(),
+            k2: C::Block::default(),
+            state: C::Block::default(),
+            last: C::Block::default(),
+            last_len: 0,
         };
         a.make_k();
         a
@@ -25,48 +56,112 @@ impl<'k> Kuznechik<'k> for AlgMac<'k> {
 
     fn set_gamma(&mut self, _gamma: Vec<u8>) { }
 
-    fn encrypt(&mut self, mut data: Vec<u8>) -> Vec<u8> {
-        let is_added = addition_block128_3(&mut data, 16);
-        let count_blocks = data.len() / 16;
+    fn try_encrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        self.update(&data);
+        Ok(self.finalize())
+    }
+
+    fn try_decrypt(&mut self, _data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        Err(KuznechikError::UnsupportedOperation)
+    }
+}
 
-        let mut result: Block128 = data[..16].try_into().unwrap();
-        encrypt_block(&mut result, &self.kuz.keys);
+impl<'k, C: BlockCipher> AlgMac<'k, C> {
+    pub fn tag_len(mut self, tag_len: usize) -> Result<Self, KuznechikError> {
+        self.set_tag_len(tag_len)?;
+        Ok(self)
+    }
 
-        for i in 1..count_blocks-1 {
-            sum_mod_2(&mut result, &data[16*i..16*(i+1)]);
-            encrypt_block(&mut result, &self.kuz.keys);
+    /// Длина имитовставки s от 1 до n байт, по умолчанию n/2 / Tag length s from 1 to n bytes, n/2 by default
+    pub fn set_tag_len(&mut self, tag_len: usize) -> Result<(), KuznechikError> {
+        if !(1..=C::BLOCK_SIZE).contains(&tag_len) {
+            return Err(KuznechikError::InvalidTagLength);
         }
+        self.s = tag_len;
+        Ok(())
+    }
+
+    /// Очередная часть сообщения / Next part of the message
+    pub fn update(&mut self, mut data: &[u8]) {
+        let n = C::BLOCK_SIZE;
+        while !data.is_empty() {
+            // Полный блок обрабатывается, только когда известно, что он не последний
+            if self.last_len == n {
+                sum_mod_2(self.state.as_mut(), self.last.as_ref());
+                self.cipher.encrypt_block(&mut self.state);
+                self.last_len = 0;
+            }
+
+            let len = (n - self.last_len).min(data.len());
+            self.last.as_mut()[self.last_len..self.last_len + len].copy_from_slice(&data[..len]);
+            self.last_len += len;
+            data = &data[len..];
+        }
+    }
 
-        let key = match is_added {
-            false => &self.k1,
-            true => &self.k2
+    /// Имитовставка MSB_s последнего блока; состояние сбрасывается для нового сообщения
+    /// The tag of the message so far; the state is reset for the next message
+    pub fn finalize(&mut self) -> Vec<u8> {
+        let n = C::BLOCK_SIZE;
+
+        // Процедура дополнения 3 и выбор K1 / K2
+        let key = match self.last_len == n {
+            true => &self.k1,
+            false => {
+                let last = self.last.as_mut();
+                last[self.last_len] = 0x80;
+                last[self.last_len + 1..].fill(0);
+                &self.k2
+            }
         };
-        sum_mod_2(&mut result, &data[16*(count_blocks-1)..]);
-        sum_mod_2(&mut result, key);
-        encrypt_block(&mut result, &self.kuz.keys);
+        sum_mod_2(self.state.as_mut(), self.last.as_ref());
+        sum_mod_2(self.state.as_mut(), key.as_ref());
+        self.cipher.encrypt_block(&mut self.state);
 
-        result[..self.s].to_vec()
+        let tag = self.state.as_ref()[..self.s].to_vec();
+        self.reset();
+        tag
     }
 
-    fn decrypt(&mut self, _data: Vec<u8>) -> Vec<u8> {
-        panic!("AlmMac has not decrypt func");
+    /// Проверка имитовставки за постоянное время / Constant-time tag check
+    pub fn verify(&mut self, tag: &[u8]) -> Result<(), KuznechikError> {
+        let mut expected = self.finalize();
+        let is_equal = ct_eq(&expected, tag);
+        expected.zeroize();
+
+        match is_equal {
+            true => Ok(()),
+            false => Err(KuznechikError::AuthenticationFailed),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state.as_mut().zeroize();
+        self.last.as_mut().zeroize();
+        self.last_len = 0;
     }
-}
 
-impl<'k> AlgMac<'k> {
     fn make_k(&mut self) {
-        self.k1 = [0u8; 16];
-        encrypt_block(&mut self.k1, &self.kuz.keys);
-        mk_k(&mut self.k1);
+        self.k1 = C::Block::default();
+        self.cipher.encrypt_block(&mut self.k1);
+        mk_k(self.k1.as_mut(), C::MAC_CONST);
 
-        self.k2 = self.k1.clone();
-        mk_k(&mut self.k2);
+        self.k2 = self.k1;
+        mk_k(self.k2.as_mut(), C::MAC_CONST);
     }
 }
 
-fn mk_k(k: &mut Block128) {
+impl<'k, C: BlockCipher> Drop for AlgMac<'k, C> {
+    fn drop(&mut self) {
+        self.k1.as_mut().zeroize();
+        self.k2.as_mut().zeroize();
+        self.reset();
+    }
+}
+
+pub(crate) fn mk_k(k: &mut [u8], b: u8) {
     if shift_left(k) == 1 {
-        k[15] ^= 0x87;
+        k[k.len() - 1] ^= b;
     };
 }
 
@@ -82,15 +177,14 @@ fn shift_left(m: &mut [u8]) -> u8 {
     h
 }
 
-fn addition_block128_3(data: &mut Vec<u8>, s: usize) -> bool {
+/// Процедура дополнения 3: неполный (или пустой) последний блок
+/// дополняется единичным битом и нулями до длины s
+pub(crate) fn addition_block_3(data: &mut Vec<u8>, s: usize) -> bool {
     let r = data.len() % s;
-    if r > 0 {
-        let mut ex_data = Vec::<u8>::with_capacity(r);
-        ex_data.push(0x80);
-        for _ in 1..r {
-            ex_data.push(0x00);
-        }
-        data.append(&mut ex_data);
+    if r > 0 || data.is_empty() {
+        let len = data.len();
+        data.resize(len + s - r, 0);
+        data[len] = 0x80;
         return true;
     }
     false
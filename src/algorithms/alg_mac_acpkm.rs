@@ -0,0 +1,118 @@
+use alloc::{vec, vec::Vec};
+
+use super::Kuznechik;
+use super::cipher::{BlockCipher, MasterKeyInit};
+use super::alg_ctr_acpkm::AlgCtrAcpkm;
+use super::alg_mac::{mk_k, addition_block_3};
+use crate::KeyStore;
+use zeroize::Zeroize;
+use crate::types::Block256;
+use crate::transforms::sum_mod_2;
+use crate::error::KuznechikError;
+
+/// Длина секции по умолчанию в байтах / Default section size in bytes
+const DEFAULT_SECTION_SIZE: usize = 4096;
+
+///
+/// Режим выработки имитовставки с внутренней сменой ключа OMAC-ACPKM (Р 1323565.1.017-2018, RFC 8645)
+/// OMAC with ACPKM-Master key derivation
+///
+/// Для каждой секции из N байт ключ K^i и значение K^i_1 вырабатываются преобразованием ACPKM-Master:
+/// гаммированием CTR-ACPKM (секция T*, IV = 1^(n/2)) нулевой строки.
+/// Имитовставка имеет длину блока n / The tag is one full block long.
+///
+pub struct AlgMacAcpkm<'k, C: MasterKeyInit = KeyStore> {
+    cipher: &'k C,
+    section_size: usize,
+    master_section_size: usize,
+}
+
+impl<'k, C: MasterKeyInit> Kuznechik<'k, C> for AlgMacAcpkm<'k, C> {
+    fn new(cipher: &'k C) -> Self {
+        AlgMacAcpkm {
+            cipher,
+            section_size: DEFAULT_SECTION_SIZE,
+            master_section_size: DEFAULT_SECTION_SIZE,
+        }
+    }
+
+    fn set_gamma(&mut self, _gamma: Vec<u8>) { }
+
+    fn try_encrypt(&mut self, mut data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        let n = C::BLOCK_SIZE;
+        let blocks_per_section = self.section_size / n;
+
+        let mut master = AlgCtrAcpkm::new(self.cipher)
+            .section_size(self.master_section_size)?
+            .gamma(vec![0xff; n / 2]);
+
+        let is_added = addition_block_3(&mut data, n);
+        let count_blocks = data.len() / n;
+
+        let mut result = C::Block::default();
+        let mut section_key = None;
+        let mut k1 = C::Block::default();
+        for (i, block) in data.chunks(n).enumerate() {
+            if i % blocks_per_section == 0 {
+                let mut material = master.try_encrypt(vec![0; 32 + n])?;
+                let mut key: Block256 = [0u8; 32];
+                key.copy_from_slice(&material[..32]);
+                section_key = Some(self.cipher.rekeyed(key));
+                k1.as_mut().copy_from_slice(&material[32..]);
+                key.zeroize();
+                material.zeroize();
+            }
+
+            sum_mod_2(result.as_mut(), block);
+            if i == count_blocks - 1 {
+                if is_added {
+                    mk_k(k1.as_mut(), C::MAC_CONST);
+                }
+                sum_mod_2(result.as_mut(), k1.as_ref());
+            }
+            if let Some(key) = &section_key {
+                key.encrypt_block(&mut result);
+            }
+        }
+
+        k1.as_mut().zeroize();
+        Ok(result.as_ref().to_vec())
+    }
+
+    fn try_decrypt(&mut self, _data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        Err(KuznechikError::UnsupportedOperation)
+    }
+}
+
+impl<'k, C: MasterKeyInit> AlgMacAcpkm<'k, C> {
+    pub fn section_size(mut self, section_size: usize) -> Result<Self, KuznechikError> {
+        self.set_section_size(section_size)?;
+        Ok(self)
+    }
+
+    /// Длина секции N в байтах, кратная длине блока / Section size N in bytes, a multiple of the block length
+    pub fn set_section_size(&mut self, section_size: usize) -> Result<(), KuznechikError> {
+        check_section_size::<C>(section_size)?;
+        self.section_size = section_size;
+        Ok(())
+    }
+
+    pub fn master_section_size(mut self, master_section_size: usize) -> Result<Self, KuznechikError> {
+        self.set_master_section_size(master_section_size)?;
+        Ok(self)
+    }
+
+    /// Длина секции T* преобразования ACPKM-Master в байтах / Section size T* of ACPKM-Master in bytes
+    pub fn set_master_section_size(&mut self, master_section_size: usize) -> Result<(), KuznechikError> {
+        check_section_size::<C>(master_section_size)?;
+        self.master_section_size = master_section_size;
+        Ok(())
+    }
+}
+
+fn check_section_size<C: BlockCipher>(section_size: usize) -> Result<(), KuznechikError> {
+    if section_size == 0 || !section_size.is_multiple_of(C::BLOCK_SIZE) {
+        return Err(KuznechikError::InvalidSectionSize);
+    }
+    Ok(())
+}
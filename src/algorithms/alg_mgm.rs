@@ -0,0 +1,181 @@
+use alloc::vec::Vec;
+
+use super::cipher::BlockCipher;
+use crate::KeyStore;
+use crate::transforms::{sum_mod_2, ct_eq};
+use crate::error::KuznechikError;
+
+///
+/// Режим аутентифицированного шифрования MGM / Multilinear Galois Mode (R 1323565.1.026-2019, RFC 9058)
+///
+/// `seal` возвращает шифртекст с имитовставкой в конце, `open` проверяет имитовставку
+/// до расшифрования и при несовпадении возвращает ошибку, не раскрывая открытый текст.
+/// The nonce is one block long with the most significant bit cleared; it must never repeat under one key.
+///
+/// ```
+/// use kuznechik::{KeyStore, AlgMgm};
+///
+/// let kuz = KeyStore::with_password("Kuznechik");
+/// let nonce = [0x11; 16];
+///
+/// let mgm = AlgMgm::new(&kuz);
+/// let sealed = mgm.seal(&nonce, b"header", Vec::from("Hello, World!")).unwrap();
+///
+/// assert_eq!(mgm.open(&nonce, b"header", sealed.clone()).unwrap(), b"Hello, World!");
+/// assert!(mgm.open(&nonce, b"other header", sealed).is_err());
+/// ```
+///
+pub struct AlgMgm<'k, C: BlockCipher = KeyStore> {
+    cipher: &'k C,
+    tag_len: usize,
+}
+
+impl<'k, C: BlockCipher> AlgMgm<'k, C> {
+    /// Имитовставка по умолчанию - полный блок / The tag is a full block by default
+    pub fn new(cipher: &'k C) -> Self {
+        AlgMgm { cipher, tag_len: C::BLOCK_SIZE }
+    }
+
+    pub fn tag_len(mut self, tag_len: usize) -> Result<Self, KuznechikError> {
+        self.set_tag_len(tag_len)?;
+        Ok(self)
+    }
+
+    /// Длина имитовставки S в байтах, 4 <= S <= n / Tag length in bytes, from 32 bits up to the block length
+    pub fn set_tag_len(&mut self, tag_len: usize) -> Result<(), KuznechikError> {
+        if !(4..=C::BLOCK_SIZE).contains(&tag_len) {
+            return Err(KuznechikError::InvalidTagLength);
+        }
+        self.tag_len = tag_len;
+        Ok(())
+    }
+
+    /// Зашифрование и выработка имитовставки / Encrypts `data` and appends the tag
+    pub fn seal(&self, nonce: &[u8], associated_data: &[u8], mut data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        self.check_input(nonce, associated_data, &data)?;
+
+        self.apply_gamma(nonce, &mut data);
+        let tag = self.compute_tag(nonce, associated_data, &data);
+        data.extend_from_slice(&tag.as_ref()[..self.tag_len]);
+
+        Ok(data)
+    }
+
+    /// Проверка имитовставки и расшифрование / Verifies the tag and decrypts
+    pub fn open(&self, nonce: &[u8], associated_data: &[u8], mut data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+        if data.len() < self.tag_len {
+            return Err(KuznechikError::AuthenticationFailed);
+        }
+        let tag = data.split_off(data.len() - self.tag_len);
+        self.check_input(nonce, associated_data, &data)?;
+
+        let expected = self.compute_tag(nonce, associated_data, &data);
+        if !ct_eq(&expected.as_ref()[..self.tag_len], &tag) {
+            return Err(KuznechikError::AuthenticationFailed);
+        }
+
+        self.apply_gamma(nonce, &mut data);
+        Ok(data)
+    }
+
+    /// Nonce - n-1 бит, старший бит блока равен нулю; длины A и C в битах помещаются в n/2 бит
+    fn check_input(&self, nonce: &[u8], associated_data: &[u8], data: &[u8]) -> Result<(), KuznechikError> {
+        if nonce.len() != C::BLOCK_SIZE || nonce[0] & 0x80 != 0 {
+            return Err(KuznechikError::InvalidNonce);
+        }
+
+        let half_bits = C::BLOCK_SIZE as u32 * 4;
+        let total_bits = (associated_data.len() as u128 + data.len() as u128) * 8;
+        if total_bits >> half_bits != 0 {
+            return Err(KuznechikError::InvalidDataLength);
+        }
+        Ok(())
+    }
+
+    /// Гаммирование: Y_1 = E(0 || nonce), Y_{i+1} = incr_r(Y_i)
+    fn apply_gamma(&self, nonce: &[u8], data: &mut [u8]) {
+        let n = C::BLOCK_SIZE;
+
+        let mut y = C::Block::default();
+        y.as_mut().copy_from_slice(nonce);
+        self.cipher.encrypt_block(&mut y);
+
+        for chunk in data.chunks_mut(n) {
+            let mut gamma = y;
+            self.cipher.encrypt_block(&mut gamma);
+            sum_mod_2(chunk, gamma.as_ref());
+            incr(&mut y.as_mut()[n / 2..]);
+        }
+    }
+
+    /// Имитовставка: E(Σ H_i ⊗ A_i ⊕ Σ H_{h+j} ⊗ C_j ⊕ H_{h+q+1} ⊗ (len(A) || len(C))),
+    /// H_i = E(Z_i), Z_1 = E(1 || nonce), Z_{i+1} = incr_l(Z_i)
+    fn compute_tag(&self, nonce: &[u8], associated_data: &[u8], data: &[u8]) -> C::Block {
+        let n = C::BLOCK_SIZE;
+
+        let mut z = C::Block::default();
+        z.as_mut().copy_from_slice(nonce);
+        z.as_mut()[0] |= 0x80;
+        self.cipher.encrypt_block(&mut z);
+
+        let mut sum = 0u128;
+        let blocks = associated_data.chunks(n).chain(data.chunks(n));
+        for chunk in blocks {
+            sum ^= self.next_h_mul(&mut z, chunk);
+        }
+
+        let mut lengths = C::Block::default();
+        let len_bits = ((associated_data.len() as u128 * 8) << (n * 4)) | (data.len() as u128 * 8);
+        lengths.as_mut().copy_from_slice(&len_bits.to_be_bytes()[16 - n..]);
+        sum ^= self.next_h_mul(&mut z, lengths.as_ref());
+
+        let mut tag = C::Block::default();
+        tag.as_mut().copy_from_slice(&sum.to_be_bytes()[16 - n..]);
+        self.cipher.encrypt_block(&mut tag);
+        tag
+    }
+
+    /// H_i ⊗ block (неполный блок дополняется нулями справа), Z := incr_l(Z)
+    fn next_h_mul(&self, z: &mut C::Block, block: &[u8]) -> u128 {
+        let n = C::BLOCK_SIZE;
+
+        let mut h = *z;
+        self.cipher.encrypt_block(&mut h);
+        incr(&mut z.as_mut()[..n / 2]);
+
+        let mut padded = C::Block::default();
+        padded.as_mut()[..block.len()].copy_from_slice(block);
+
+        gf_mul(to_u128(h.as_ref()), to_u128(padded.as_ref()), n as u32 * 8, C::MAC_CONST as u128)
+    }
+}
+
+/// Увеличение половины блока на 1 по модулю 2^(n/2)
+fn incr(half: &mut [u8]) {
+    for i in (0..half.len()).rev() {
+        half[i] = half[i].wrapping_add(1);
+        if half[i] != 0 {
+            break;
+        }
+    }
+}
+
+fn to_u128(block: &[u8]) -> u128 {
+    block.iter().fold(0u128, |acc, &b| (acc << 8) | b as u128)
+}
+
+/// Умножение в поле GF(2^bits) по модулю x^bits + poly, без ветвлений по данным
+/// (x^128 + x^7 + x^2 + x + 1 для n = 128, x^64 + x^4 + x^3 + x + 1 для n = 64)
+fn gf_mul(mut a: u128, b: u128, bits: u32, poly: u128) -> u128 {
+    let mask = if bits == 128 { !0u128 } else { (1u128 << bits) - 1 };
+    let mut res = 0u128;
+
+    for i in 0..bits {
+        let bit = (b >> i) & 1;
+        res ^= a & bit.wrapping_neg();
+
+        let carry = (a >> (bits - 1)) & 1;
+        a = ((a << 1) & mask) ^ (poly & carry.wrapping_neg());
+    }
+    res
+}
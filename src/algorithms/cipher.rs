@@ -0,0 +1,134 @@
+use zeroize::Zeroize;
+
+use crate::types::Block256;
+
+///
+/// Блочный шифр режима / Block cipher used by the encryption modes
+///
+/// Режимы ГОСТ Р 34.13-2015 (`AlgEcb` ... `AlgMac`) обобщены по этому трейту,
+/// по умолчанию используется "Кузнечик" ([`KeyStore`](crate::KeyStore)), также реализован для "Магмы" ([`MagmaKeyStore`](crate::MagmaKeyStore)).
+/// Implement it to run the modes over another cipher, e.g. a hardware-backed one.
+///
+/// ```
+/// use kuznechik::{BlockCipher, KeyStore, Kuznechik, AlgCtr};
+///
+/// // A cipher that delegates to the software Kuznechik, standing in for a hardware token
+/// struct Token(KeyStore);
+///
+/// impl BlockCipher for Token {
+///     type Block = [u8; 16];
+///     const BLOCK_SIZE: usize = 16;
+///
+///     fn encrypt_block(&self, block: &mut [u8; 16]) {
+///         self.0.encrypt_block(block)
+///     }
+///
+///     fn decrypt_block(&self, block: &mut [u8; 16]) {
+///         self.0.decrypt_block(block)
+///     }
+/// }
+///
+/// let token = Token(KeyStore::with_password("Kuznechik"));
+/// let kuz = KeyStore::with_password("Kuznechik");
+/// let gamma = vec![0x12; 16];
+///
+/// let enc_data = AlgCtr::new(&token).gamma(gamma.clone()).encrypt(Vec::from("Hello, World!"));
+/// assert_eq!(enc_data, AlgCtr::new(&kuz).gamma(gamma).encrypt(Vec::from("Hello, World!")));
+/// ```
+///
+pub trait BlockCipher {
+    /// Блок шифра / Cipher block, `[u8; BLOCK_SIZE]`
+    type Block: Copy + Default + AsRef<[u8]> + AsMut<[u8]>;
+
+    /// Длина блока n в байтах / Block length n in bytes
+    const BLOCK_SIZE: usize;
+
+    /// Константа B_n для выработки вспомогательных ключей имитовставки (ГОСТ Р 34.13-2015, 5.6.3)
+    /// MAC subkey constant; the default covers n = 64 and n = 128
+    const MAC_CONST: u8 = if Self::BLOCK_SIZE == 8 { 0x1b } else { 0x87 };
+
+    fn encrypt_block(&self, block: &mut Self::Block);
+    fn decrypt_block(&self, block: &mut Self::Block);
+
+    /// Зашифрование подряд идущих блоков, `data.len()` кратна `BLOCK_SIZE`; реализация может обрабатывать их одновременно
+    /// Encrypts consecutive blocks in place; override to process several blocks at once
+    fn encrypt_blocks(&self, data: &mut [u8]) {
+        let mut block = Self::Block::default();
+        for chunk in data.chunks_exact_mut(Self::BLOCK_SIZE) {
+            block.as_mut().copy_from_slice(chunk);
+            self.encrypt_block(&mut block);
+            chunk.copy_from_slice(block.as_ref());
+        }
+    }
+
+    fn decrypt_blocks(&self, data: &mut [u8]) {
+        let mut block = Self::Block::default();
+        for chunk in data.chunks_exact_mut(Self::BLOCK_SIZE) {
+            block.as_mut().copy_from_slice(chunk);
+            self.decrypt_block(&mut block);
+            chunk.copy_from_slice(block.as_ref());
+        }
+    }
+}
+
+///
+/// Шифр, создаваемый из 256-битного ключа / Block cipher that can be keyed from a 256-bit master key
+///
+/// Нужен режимам со сменой ключа (ACPKM) и `CtrOmac`, которые вырабатывают новый ключ из текущего.
+///
+pub trait MasterKeyInit: BlockCipher + Sized {
+    fn from_master_key(master_key: Block256) -> Self;
+
+    /// Новый ключ с настройками `self` / A new key keeping the settings of `self`, e.g. the `KeyStore` backend
+    fn rekeyed(&self, master_key: Block256) -> Self {
+        Self::from_master_key(master_key)
+    }
+}
+
+/// Длина пачки блоков, передаваемой в `encrypt_blocks` режимами CTR, CBC и CFB; с `rayon` её делят между потоками
+pub(crate) const BATCH_LEN: usize = if cfg!(feature = "rayon") { 1 << 20 } else { 512 };
+
+/// Пачки не длиннее этого размещаются на стеке / Batches up to this length live on the stack
+const STACK_LEN: usize = 512;
+
+/// Вызывает `f` с обнулённым буфером длины `len`: на стеке, если он помещается (всегда без `rayon`), иначе в куче.
+/// Буфер затирается после использования.
+pub(crate) fn with_scratch<R>(len: usize, f: impl FnOnce(&mut [u8]) -> R) -> R {
+    #[cfg(feature = "rayon")]
+    if len > STACK_LEN {
+        let mut buf = std::vec![0u8; len];
+        let result = f(&mut buf);
+        buf.zeroize();
+        return result;
+    }
+
+    let mut buf = [0u8; STACK_LEN];
+    let result = f(&mut buf[..len]);
+    buf.zeroize();
+    result
+}
+
+/// Часть буфера, обрабатываемая одним потоком / Bytes handed to one thread
+#[cfg(feature = "rayon")]
+const PAR_CHUNK_LEN: usize = 64 << 10;
+
+/// Обрабатывает блоки буфера: с функцией `rayon` крупные буферы делятся на части по потокам
+/// `f` receives whole blocks; with the `rayon` feature large buffers are split across threads
+pub(crate) fn for_each_chunk(data: &mut [u8], f: impl Fn(&mut [u8]) + Sync) {
+    #[cfg(feature = "rayon")]
+    if data.len() > PAR_CHUNK_LEN {
+        use rayon::prelude::*;
+        data.par_chunks_mut(PAR_CHUNK_LEN).for_each(&f);
+        return;
+    }
+    f(data)
+}
+
+/// Копирует блок из среза, обрабатывает его и записывает обратно
+#[inline]
+pub(crate) fn encrypt_slice<C: BlockCipher>(cipher: &C, data: &mut [u8]) {
+    let mut block = C::Block::default();
+    block.as_mut().copy_from_slice(data);
+    cipher.encrypt_block(&mut block);
+    data.copy_from_slice(block.as_ref());
+}
@@ -0,0 +1,101 @@
+use core::ops::{Deref, DerefMut};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use zeroize::Zeroize;
+
+use crate::error::KuznechikError;
+
+/// Наибольшая длина гаммы (синхропосылки) без функции `alloc`; с ней длина не ограничена.
+/// Longest gamma (IV) register without the `alloc` feature, e.g. 4 Kuznechik or 8 Magma blocks for CBC, OFB and CFB.
+pub const MAX_GAMMA_LEN: usize = 64;
+
+/// Регистр гаммы режима, затирается при замене и удалении
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+pub(crate) struct Gamma(Vec<u8>);
+
+#[cfg(not(feature = "alloc"))]
+pub(crate) struct Gamma {
+    buf: [u8; MAX_GAMMA_LEN],
+    len: usize,
+}
+
+#[cfg(not(feature = "alloc"))]
+impl Default for Gamma {
+    fn default() -> Self {
+        Gamma { buf: [0u8; MAX_GAMMA_LEN], len: 0 }
+    }
+}
+
+impl Gamma {
+    /// Копирует гамму из среза, прежнее значение затирается
+    pub(crate) fn set(&mut self, gamma: &[u8]) -> Result<(), KuznechikError> {
+        self.set_with_zeros(gamma, 0)
+    }
+
+    /// Гамма, дополненная `zeros` нулевыми байтами (счётчик CTR-ACPKM)
+    #[cfg(feature = "alloc")]
+    pub(crate) fn set_with_zeros(&mut self, gamma: &[u8], zeros: usize) -> Result<(), KuznechikError> {
+        self.0.zeroize();
+        self.0.extend_from_slice(gamma);
+        self.0.resize(gamma.len() + zeros, 0);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    pub(crate) fn set_with_zeros(&mut self, gamma: &[u8], zeros: usize) -> Result<(), KuznechikError> {
+        let len = gamma.len() + zeros;
+        if len > MAX_GAMMA_LEN {
+            return Err(KuznechikError::InvalidGammaLength);
+        }
+        self.buf.zeroize();
+        self.buf[..gamma.len()].copy_from_slice(gamma);
+        self.len = len;
+        Ok(())
+    }
+
+    #[cfg(feature = "alloc")]
+    pub(crate) fn replace(&mut self, gamma: Vec<u8>) {
+        self.0.zeroize();
+        self.0 = gamma;
+    }
+}
+
+impl Deref for Gamma {
+    type Target = [u8];
+
+    #[cfg(feature = "alloc")]
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn deref(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl DerefMut for Gamma {
+    #[cfg(feature = "alloc")]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.buf[..self.len]
+    }
+}
+
+impl Drop for Gamma {
+    #[cfg(feature = "alloc")]
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn drop(&mut self) {
+        self.buf.zeroize();
+    }
+}
@@ -0,0 +1,152 @@
+use std::io::{self, Read, Write};
+use super::stream::{StreamMode, Encryptor, Decryptor};
+use crate::error::KuznechikError;
+
+const CHUNK_LEN: usize = 8192;
+
+///
+/// Шифрование при записи / Encrypting [`Write`] adapter
+///
+/// Шифртекст целых сегментов сразу записывается во вложенный поток.
+/// Последний сегмент (и дополнение) записывается только в `finish`, который нужно вызвать явно.
+/// `flush` does not finalize the stream, it only flushes what has already been encrypted.
+///
+/// Режим продвигается до записи во вложенный поток, поэтому после ошибки записи повторить её нельзя:
+/// дальнейшие `write` и `finish` возвращают ошибку. After an error of the inner writer the stream is lost;
+/// start over with a new writer and a fresh gamma.
+///
+/// ```
+/// use std::io::Write;
+/// use kuznechik::{KeyStore, Kuznechik, AlgCtr, EncryptWriter};
+///
+/// let kuz = KeyStore::with_password("Kuznechik");
+/// let gamma = vec![0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xce, 0xf0, 0, 0, 0, 0, 0, 0, 0, 0];
+///
+/// let mut writer = EncryptWriter::new(Vec::new(), AlgCtr::new(&kuz).gamma(gamma)).unwrap();
+/// writer.write_all(b"Hello, World!").unwrap();
+/// let enc_data = writer.finish().unwrap();
+///
+/// assert_eq!(enc_data.len(), 13);
+/// ```
+///
+pub struct EncryptWriter<W: Write, M: StreamMode> {
+    inner: W,
+    encryptor: Encryptor<M>,
+    buffer: Vec<u8>,
+    poisoned: bool,
+}
+
+impl<W: Write, M: StreamMode> EncryptWriter<W, M> {
+    pub fn new(inner: W, mode: M) -> Result<Self, KuznechikError> {
+        Ok(EncryptWriter {
+            inner,
+            encryptor: Encryptor::new(mode)?,
+            buffer: Vec::new(),
+            poisoned: false,
+        })
+    }
+
+    /// Дописывает последний сегмент и возвращает вложенный поток
+    pub fn finish(mut self) -> io::Result<W> {
+        self.check_poisoned()?;
+        self.buffer.clear();
+        self.encryptor.finalize(&mut self.buffer)?;
+        self.inner.write_all(&self.buffer)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn check_poisoned(&self) -> io::Result<()> {
+        if self.poisoned {
+            return Err(io::Error::other("EncryptWriter is unusable after a failed write"));
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write, M: StreamMode> Write for EncryptWriter<W, M> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_poisoned()?;
+        self.buffer.clear();
+        self.encryptor.update(buf, &mut self.buffer)?;
+        // Гамма уже продвинута, повтор записи испортил бы поток / The gamma has moved on, a retry would corrupt the stream
+        if let Err(e) = self.inner.write_all(&self.buffer) {
+            self.poisoned = true;
+            return Err(e);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+///
+/// Расшифрование при чтении / Decrypting [`Read`] adapter
+///
+/// Дополнение проверяется по достижении конца вложенного потока,
+/// ошибка расшифрования возвращается как [`io::ErrorKind::InvalidData`].
+///
+pub struct DecryptReader<R: Read, M: StreamMode> {
+    inner: R,
+    decryptor: Option<Decryptor<M>>,
+    buffer: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read, M: StreamMode> DecryptReader<R, M> {
+    pub fn new(inner: R, mode: M) -> Result<Self, KuznechikError> {
+        Ok(DecryptReader {
+            inner,
+            decryptor: Some(Decryptor::new(mode)?),
+            buffer: Vec::new(),
+            pos: 0,
+        })
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn fill_buffer(&mut self) -> io::Result<()> {
+        let mut chunk = [0u8; CHUNK_LEN];
+        self.buffer.clear();
+        self.pos = 0;
+
+        while self.buffer.is_empty() {
+            let decryptor = match self.decryptor.as_mut() {
+                Some(d) => d,
+                None => return Ok(()),
+            };
+
+            let n = match self.inner.read(&mut chunk) {
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            if n == 0 {
+                if let Some(d) = self.decryptor.take() {
+                    d.finalize(&mut self.buffer)?;
+                }
+            } else {
+                decryptor.update(&chunk[..n], &mut self.buffer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read, M: StreamMode> Read for DecryptReader<R, M> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.buffer.len() {
+            self.fill_buffer()?;
+        }
+
+        let n = buf.len().min(self.buffer.len() - self.pos);
+        buf[..n].copy_from_slice(&self.buffer[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
@@ -1,19 +1,57 @@
+mod cipher;
+mod gamma;
 mod alg_ecb;
 mod alg_ctr;
+mod alg_ctr_acpkm;
 mod alg_ofb;
 mod alg_cbc;
 mod alg_cfb;
+#[cfg(feature = "alloc")]
 mod alg_mac;
+#[cfg(feature = "alloc")]
+mod alg_mac_acpkm;
+#[cfg(feature = "alloc")]
+mod alg_mgm;
+#[cfg(feature = "alloc")]
+mod alg_ctr_omac;
+mod stream;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "getrandom")]
+mod random;
 mod tests;
 
+pub use cipher::{BlockCipher, MasterKeyInit};
+pub(crate) use cipher::for_each_chunk;
+pub use gamma::MAX_GAMMA_LEN;
 pub use alg_ecb::AlgEcb;
 pub use alg_ctr::AlgCtr;
+pub use alg_ctr_acpkm::AlgCtrAcpkm;
 pub use alg_ofb::AlgOfb;
 pub use alg_cbc::AlgCbc;
 pub use alg_cfb::AlgCfb;
+#[cfg(feature = "alloc")]
 pub use alg_mac::AlgMac;
+#[cfg(feature = "alloc")]
+pub use alg_mac_acpkm::AlgMacAcpkm;
+#[cfg(feature = "alloc")]
+pub use alg_mgm::AlgMgm;
+#[cfg(feature = "alloc")]
+pub use alg_ctr_omac::CtrOmac;
+pub use stream::StreamMode;
+#[cfg(feature = "alloc")]
+pub use stream::{Encryptor, Decryptor};
+#[cfg(feature = "std")]
+pub use io::{EncryptWriter, DecryptReader};
+#[cfg(feature = "getrandom")]
+pub use random::RandomIv;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 
 use crate::key_store::KeyStore;
+#[cfg(feature = "alloc")]
+use crate::error::KuznechikError;
 
 
 ///
@@ -31,6 +69,8 @@ use crate::key_store::KeyStore;
 /// | AlgCbc | Cipher Block Chaining       |    СВС    |
 /// | AlgCfb | Cipher Feedback             |    CFB    |
 /// | AlgMac | Message Authentication Code |    MAC    |
+/// | AlgMgm | Multilinear Galois Mode     |    MGM    |
+/// | CtrOmac | Encrypt-then-MAC (CTR + OMAC) | CTR + MAC |
 ///
 /// ## Usage (AlgOfb):
 /// The following example encrypts and decrypts a 64-byte data block `data` using the OFB method.
@@ -70,16 +110,39 @@ use crate::key_store::KeyStore;
 /// }
 /// ```
 ///
+/// ## Errors
+/// `encrypt` and `decrypt` panic on invalid input (short gamma, truncated ciphertext,
+/// broken padding). Use `try_encrypt` and `try_decrypt` to get a [`KuznechikError`] instead.
+///
+/// ## no_std
+/// Методы на `Vec` требуют функции `alloc`. Без неё доступен `new`, а гамма и шифрование на месте - через [`StreamMode`].
+/// The `Vec` methods need the `alloc` feature; without it use `new` and the slice API of [`StreamMode`].
+///
+pub trait Kuznechik<'k, C: BlockCipher = KeyStore> : Sized {
+    fn new(cipher: &'k C) -> Self;
 
-pub trait Kuznechik<'k> : Sized {
-    fn new(kuz: &'k KeyStore) -> Self;
-
+    #[cfg(feature = "alloc")]
     fn gamma(mut self, gamma: Vec<u8>) -> Self {
         self.set_gamma(gamma);
         self
     }
 
+    #[cfg(feature = "alloc")]
     fn set_gamma(&mut self, gamma: Vec<u8>);
-    fn encrypt(&mut self, data: Vec<u8>) -> Vec<u8>;
-    fn decrypt(&mut self, data: Vec<u8>) -> Vec<u8>;
+    #[cfg(feature = "alloc")]
+    fn try_encrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError>;
+    #[cfg(feature = "alloc")]
+    fn try_decrypt(&mut self, data: Vec<u8>) -> Result<Vec<u8>, KuznechikError>;
+
+    #[cfg(feature = "alloc")]
+    fn encrypt(&mut self, data: Vec<u8>) -> Vec<u8> {
+        self.try_encrypt(data)
+            .unwrap_or_else(|err| panic!("encrypt(): {}", err))
+    }
+
+    #[cfg(feature = "alloc")]
+    fn decrypt(&mut self, data: Vec<u8>) -> Vec<u8> {
+        self.try_decrypt(data)
+            .unwrap_or_else(|err| panic!("decrypt(): {}", err))
+    }
 }
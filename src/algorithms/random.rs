@@ -0,0 +1,104 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::{Kuznechik, AlgCbc, AlgCfb, AlgCtr, AlgCtrAcpkm, AlgMgm, AlgOfb, CtrOmac};
+use super::cipher::{BlockCipher, MasterKeyInit};
+use super::stream::StreamMode;
+use crate::KeyStore;
+use crate::error::KuznechikError;
+
+///
+/// Режимы со случайной синхропосылкой / Modes that can start from a fresh random IV
+///
+/// Синхропосылка берётся из генератора ОС (`getrandom`) и имеет длину, требуемую ГОСТ Р 34.13-2015:
+///
+/// | Режим / Mode | Синхропосылка / IV |
+/// |:--|:--|
+/// | AlgCbc | n (m = n) |
+/// | AlgCtr | n/2, гамма - счётчик `IV \|\| 0^(n/2)` / the gamma is the counter `IV \|\| 0^(n/2)` |
+/// | AlgCtrAcpkm | n/2 |
+/// | AlgOfb, AlgCfb | n (m = n) |
+///
+/// `with_random_iv` возвращает режим и гамму, которую нужно передать получателю вместе с шифртекстом;
+/// получатель расшифровывает с `gamma(iv)` / `try_gamma(&iv)`.
+/// A new IV must be generated for every message: never encrypt twice with one IV under one key.
+///
+/// ```
+/// use kuznechik::{KeyStore, Kuznechik, AlgCbc, RandomIv};
+///
+/// let kuz = KeyStore::with_password("Kuznechik");
+///
+/// let (mut cipher, iv) = AlgCbc::with_random_iv(&kuz).unwrap();
+/// let enc_data = cipher.encrypt(Vec::from("Hello, World!"));
+///
+/// // IV передаётся вместе с шифртекстом / The IV is sent along with the ciphertext
+/// let dec_data = AlgCbc::new(&kuz).gamma(iv).decrypt(enc_data);
+/// assert_eq!(dec_data, b"Hello, World!");
+/// ```
+///
+pub trait RandomIv<'k, C: BlockCipher = KeyStore>: Kuznechik<'k, C> + StreamMode {
+    /// Случайная гамма длины, требуемой режимом / A random gamma of the length the mode requires
+    fn random_iv() -> Result<Vec<u8>, KuznechikError>;
+
+    /// Режим со случайной гаммой и сама гамма / The mode keyed with a random gamma, and that gamma
+    fn with_random_iv(cipher: &'k C) -> Result<(Self, Vec<u8>), KuznechikError> {
+        let iv = Self::random_iv()?;
+        let mode = Self::new(cipher).try_gamma(&iv)?;
+        Ok((mode, iv))
+    }
+}
+
+impl<'k, C: BlockCipher> RandomIv<'k, C> for AlgCbc<'k, C> {
+    fn random_iv() -> Result<Vec<u8>, KuznechikError> {
+        random_bytes(C::BLOCK_SIZE)
+    }
+}
+
+impl<'k, C: BlockCipher> RandomIv<'k, C> for AlgCtr<'k, C> {
+    /// IV || 0^(n/2)
+    fn random_iv() -> Result<Vec<u8>, KuznechikError> {
+        let mut counter = random_bytes(C::BLOCK_SIZE / 2)?;
+        counter.resize(C::BLOCK_SIZE, 0);
+        Ok(counter)
+    }
+}
+
+impl<'k, C: MasterKeyInit> RandomIv<'k, C> for AlgCtrAcpkm<'k, C> {
+    fn random_iv() -> Result<Vec<u8>, KuznechikError> {
+        random_bytes(C::BLOCK_SIZE / 2)
+    }
+}
+
+impl<'k, C: BlockCipher> RandomIv<'k, C> for AlgOfb<'k, C> {
+    fn random_iv() -> Result<Vec<u8>, KuznechikError> {
+        random_bytes(C::BLOCK_SIZE)
+    }
+}
+
+impl<'k, C: BlockCipher> RandomIv<'k, C> for AlgCfb<'k, C> {
+    fn random_iv() -> Result<Vec<u8>, KuznechikError> {
+        random_bytes(C::BLOCK_SIZE)
+    }
+}
+
+impl<'k, C: BlockCipher> AlgMgm<'k, C> {
+    /// Случайный nonce длины n со сброшенным старшим битом / A random one-block nonce with the top bit cleared
+    pub fn random_nonce(&self) -> Result<Vec<u8>, KuznechikError> {
+        let mut nonce = random_bytes(C::BLOCK_SIZE)?;
+        nonce[0] &= 0x7f;
+        Ok(nonce)
+    }
+}
+
+impl<C: MasterKeyInit> CtrOmac<C> {
+    /// Случайная синхропосылка длины n/2 / A random IV of half a block
+    pub fn random_iv(&self) -> Result<Vec<u8>, KuznechikError> {
+        random_bytes(C::BLOCK_SIZE / 2)
+    }
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, KuznechikError> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes).map_err(|_| KuznechikError::RandomFailed)?;
+    Ok(bytes)
+}
@@ -0,0 +1,247 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::error::KuznechikError;
+use crate::transforms::{addition_block_2_in_place, padding_start, check_data_len};
+#[cfg(feature = "alloc")]
+use crate::transforms::{addition_block_s_2, addition_rev_block_2};
+
+/// Посегментная обработка данных режимом шифрования.
+/// Segment-wise processing of data, shared by the one-shot API, [`Encryptor`] and [`Decryptor`].
+///
+/// `encrypt_segments` and `decrypt_segments` are called with whole segments only,
+/// except for the last call of modes without padding (CTR, OFB), which may get a shorter tail.
+/// The gamma must be validated with `check_gamma` before the first call.
+///
+/// Методы `try_gamma`, `*_in_place` и `*_padded` работают с буфером вызывающего без выделения памяти
+/// и доступны без функции `alloc` (no_std).
+/// `try_gamma`, `*_in_place` and `*_padded` work on caller-provided buffers, do not allocate and are available
+/// without the `alloc` feature (with `rayon` large CTR, CBC and CFB buffers use a heap batch).
+///
+/// ```
+/// use kuznechik::{KeyStore, Kuznechik, AlgCtr, AlgCbc, StreamMode};
+///
+/// let kuz = KeyStore::with_password("Kuznechik");
+///
+/// let mut packet = *b"Hello, World!";
+/// AlgCtr::new(&kuz).try_gamma(&[0x12; 16]).unwrap().encrypt_in_place(&mut packet).unwrap();
+/// assert_eq!(packet.to_vec(), AlgCtr::new(&kuz).gamma(vec![0x12; 16]).encrypt(Vec::from("Hello, World!")));
+///
+/// // Место под дополнение предоставляет вызывающий / The caller leaves room for the padding
+/// let mut buf = [0u8; 64];
+/// buf[..13].copy_from_slice(b"Hello, World!");
+/// let enc_len = AlgCbc::new(&kuz).try_gamma(&[0x34; 16]).unwrap().encrypt_padded(&mut buf, 13).unwrap().len();
+/// assert_eq!(enc_len, 16);
+///
+/// let dec_data = AlgCbc::new(&kuz).try_gamma(&[0x34; 16]).unwrap().decrypt_padded(&mut buf[..enc_len]).unwrap();
+/// assert_eq!(dec_data, b"Hello, World!");
+/// ```
+pub trait StreamMode {
+    /// Длина сегмента в байтах / Segment length in bytes
+    fn segment_len(&self) -> usize;
+    /// Дополняет ли режим последний сегмент / Whether the mode pads the last segment
+    fn is_padded(&self) -> bool;
+    fn check_gamma(&self) -> Result<(), KuznechikError>;
+    fn encrypt_segments(&mut self, data: &mut [u8]);
+    fn decrypt_segments(&mut self, data: &mut [u8]);
+
+    /// Установка гаммы из среза; без `alloc` длина ограничена [`MAX_GAMMA_LEN`](crate::MAX_GAMMA_LEN)
+    /// Sets the gamma from a slice, like `Kuznechik::set_gamma` without taking a `Vec`
+    fn try_set_gamma(&mut self, gamma: &[u8]) -> Result<(), KuznechikError>;
+
+    fn try_gamma(mut self, gamma: &[u8]) -> Result<Self, KuznechikError> where Self: Sized {
+        self.try_set_gamma(gamma)?;
+        Ok(self)
+    }
+
+    /// Зашифрование на месте без дополнения: CTR и OFB принимают любую длину,
+    /// режимы с дополнением (ECB, CBC, CFB) - только целые сегменты
+    /// Encrypts in place without padding; padded modes need a whole number of segments
+    fn encrypt_in_place(&mut self, data: &mut [u8]) -> Result<(), KuznechikError> {
+        check_in_place(self, data)?;
+        self.encrypt_segments(data);
+        Ok(())
+    }
+
+    fn decrypt_in_place(&mut self, data: &mut [u8]) -> Result<(), KuznechikError> {
+        check_in_place(self, data)?;
+        self.decrypt_segments(data);
+        Ok(())
+    }
+
+    /// Зашифрование первых `msg_len` байт буфера с дополнением; остаток буфера - место под дополнение.
+    /// Возвращает шифртекст, в режимах без дополнения он той же длины, что и сообщение.
+    /// Encrypts the first `msg_len` bytes, padding into the rest of `buf`; fails with `BufferTooSmall` if it does not fit
+    fn encrypt_padded<'a>(&mut self, buf: &'a mut [u8], msg_len: usize) -> Result<&'a [u8], KuznechikError> {
+        self.check_gamma()?;
+
+        let len = if self.is_padded() {
+            addition_block_2_in_place(buf, msg_len, self.segment_len())?
+        } else if msg_len <= buf.len() {
+            msg_len
+        } else {
+            return Err(KuznechikError::BufferTooSmall);
+        };
+        self.encrypt_segments(&mut buf[..len]);
+
+        Ok(&buf[..len])
+    }
+
+    /// Расшифрование на месте со снятием дополнения / Decrypts in place and returns the unpadded message
+    fn decrypt_padded<'a>(&mut self, data: &'a mut [u8]) -> Result<&'a [u8], KuznechikError> {
+        self.check_gamma()?;
+
+        if self.is_padded() {
+            check_data_len(data, self.segment_len())?;
+        }
+        self.decrypt_segments(data);
+
+        let len = if self.is_padded() { padding_start(data, self.segment_len())? } else { data.len() };
+        Ok(&data[..len])
+    }
+}
+
+fn check_in_place<M: StreamMode + ?Sized>(mode: &M, data: &[u8]) -> Result<(), KuznechikError> {
+    mode.check_gamma()?;
+
+    if mode.is_padded() && !data.len().is_multiple_of(mode.segment_len()) {
+        return Err(KuznechikError::InvalidDataLength);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn encrypt_all<M: StreamMode>(mode: &mut M, mut data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+    mode.check_gamma()?;
+
+    if mode.is_padded() {
+        addition_block_s_2(&mut data, mode.segment_len());
+    }
+    mode.encrypt_segments(&mut data);
+
+    Ok(data)
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn decrypt_all<M: StreamMode>(mode: &mut M, mut data: Vec<u8>) -> Result<Vec<u8>, KuznechikError> {
+    mode.check_gamma()?;
+
+    if mode.is_padded() {
+        check_data_len(&data, mode.segment_len())?;
+    }
+    mode.decrypt_segments(&mut data);
+    if mode.is_padded() {
+        addition_rev_block_2(&mut data, mode.segment_len())?;
+    }
+
+    Ok(data)
+}
+
+#[cfg(feature = "alloc")]
+///
+/// Потоковое шифрование / Streaming encryption
+///
+/// Буферизует неполный сегмент между вызовами `update`, дополнение выполняется только в `finalize`.
+/// The result is the same as the one-shot `encrypt` of the concatenated input.
+///
+/// ```
+/// use kuznechik::{KeyStore, Kuznechik, AlgCbc, Encryptor};
+///
+/// let kuz = KeyStore::with_password("Kuznechik");
+/// let gamma = vec![0x12; 32];
+///
+/// let mut enc = Encryptor::new(AlgCbc::new(&kuz).gamma(gamma.clone())).unwrap();
+/// let mut enc_data = Vec::new();
+/// enc.update(b"Hello, ", &mut enc_data).unwrap();
+/// enc.update(b"World!", &mut enc_data).unwrap();
+/// enc.finalize(&mut enc_data).unwrap();
+///
+/// let expected = AlgCbc::new(&kuz).gamma(gamma).encrypt(Vec::from("Hello, World!"));
+/// assert_eq!(enc_data, expected);
+/// ```
+///
+pub struct Encryptor<M: StreamMode> {
+    mode: M,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl<M: StreamMode> Encryptor<M> {
+    pub fn new(mode: M) -> Result<Self, KuznechikError> {
+        mode.check_gamma()?;
+        let buffer = Vec::with_capacity(mode.segment_len());
+        Ok(Encryptor { mode, buffer })
+    }
+
+    pub fn update(&mut self, data: &[u8], out: &mut Vec<u8>) -> Result<(), KuznechikError> {
+        let start = out.len();
+        take_segments(&mut self.buffer, data, self.mode.segment_len(), false, out);
+        self.mode.encrypt_segments(&mut out[start..]);
+        Ok(())
+    }
+
+    pub fn finalize(mut self, out: &mut Vec<u8>) -> Result<(), KuznechikError> {
+        if self.mode.is_padded() {
+            addition_block_s_2(&mut self.buffer, self.mode.segment_len());
+        }
+        self.mode.encrypt_segments(&mut self.buffer);
+        out.extend_from_slice(&self.buffer);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+///
+/// Потоковое расшифрование / Streaming decryption
+///
+/// В режимах с дополнением последний сегмент удерживается до вызова `finalize`,
+/// где проверяется и снимается дополнение.
+/// For padded modes the last segment is held back until `finalize`, where the padding is checked and removed.
+///
+pub struct Decryptor<M: StreamMode> {
+    mode: M,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl<M: StreamMode> Decryptor<M> {
+    pub fn new(mode: M) -> Result<Self, KuznechikError> {
+        mode.check_gamma()?;
+        let buffer = Vec::with_capacity(mode.segment_len());
+        Ok(Decryptor { mode, buffer })
+    }
+
+    pub fn update(&mut self, data: &[u8], out: &mut Vec<u8>) -> Result<(), KuznechikError> {
+        let start = out.len();
+        take_segments(&mut self.buffer, data, self.mode.segment_len(), self.mode.is_padded(), out);
+        self.mode.decrypt_segments(&mut out[start..]);
+        Ok(())
+    }
+
+    pub fn finalize(mut self, out: &mut Vec<u8>) -> Result<(), KuznechikError> {
+        if self.mode.is_padded() {
+            check_data_len(&self.buffer, self.mode.segment_len())?;
+        }
+        self.mode.decrypt_segments(&mut self.buffer);
+        if self.mode.is_padded() {
+            addition_rev_block_2(&mut self.buffer, self.mode.segment_len())?;
+        }
+        out.extend_from_slice(&self.buffer);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Переносит в `out` все целые сегменты из буфера и `data`, остаток сохраняется в буфере.
+/// При `keep_last` в буфере всегда остаётся хотя бы один байт (последний сегмент целиком).
+fn take_segments(buffer: &mut Vec<u8>, data: &[u8], s: usize, keep_last: bool, out: &mut Vec<u8>) {
+    buffer.extend_from_slice(data);
+
+    let mut count = buffer.len() / s * s;
+    if keep_last && count == buffer.len() {
+        count = count.saturating_sub(s);
+    }
+
+    out.extend_from_slice(&buffer[..count]);
+    buffer.drain(..count);
+}
@@ -1,7 +1,15 @@
-#![cfg(test)]
+#![cfg(all(test, feature = "alloc"))]
 
-use crate::KeyStore;
-use super::{Kuznechik, AlgEcb, AlgCbc, AlgCfb, AlgCtr, AlgMac, AlgOfb};
+use crate::{KeyStore, MagmaKeyStore, KuznechikError, Backend};
+use super::alg_ctr_acpkm::acpkm;
+use super::{Kuznechik, BlockCipher, AlgEcb, AlgCbc, AlgCfb, AlgCtr, AlgCtrAcpkm, AlgMac, AlgMacAcpkm, AlgMgm, AlgOfb, CtrOmac, MasterKeyInit, StreamMode, Encryptor, Decryptor};
+#[cfg(feature = "std")]
+use super::{EncryptWriter, DecryptReader};
+#[cfg(feature = "getrandom")]
+use super::RandomIv;
+
+/// Векторы проверяются на всех реализациях / Vectors are checked on every backend
+const BACKENDS: [Backend; 4] = [Backend::Table, Backend::Reference, Backend::ConstantTime, Backend::Simd];
 
 const DATA: [u8; 64] = [
     0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x00, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88,
@@ -202,3 +210,1041 @@ fn encrypt_decrypt_alg_mac() {
     // Assert
     assert_eq!(enc_data, expected_enc_data);
 }
+
+#[test]
+fn alg_mac_update_verify() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let data: Vec<u8> = DATA.iter().cycle().take(100).copied().collect();
+
+    for len in [0, 1, 15, 16, 17, 32, 100] {
+        let expected = AlgMac::new(&kuz).tag_len(16).unwrap().encrypt(data[..len].to_vec());
+        let mut mac = AlgMac::new(&kuz).tag_len(16).unwrap();
+
+        for split in 0..=len {
+            // Act
+            mac.update(&data[..split]);
+            mac.update(&data[split..len]);
+            let tag = mac.finalize();
+
+            mac.update(&data[..len]);
+            let verified = mac.verify(&tag);
+
+            mac.update(&data[..len]);
+            let forged = mac.verify(&[tag[0] ^ 1; 16]);
+
+            // Assert
+            assert_eq!(tag, expected);
+            assert_eq!(verified, Ok(()));
+            assert_eq!(forged, Err(KuznechikError::AuthenticationFailed));
+        }
+    }
+
+    assert_eq!(AlgMac::new(&kuz).tag_len(4).unwrap().encrypt(DATA.to_vec()), AlgMac::new(&kuz).encrypt(DATA.to_vec())[..4]);
+    assert_eq!(AlgMac::new(&kuz).tag_len(0).err(), Some(KuznechikError::InvalidTagLength));
+    assert_eq!(AlgMac::new(&kuz).tag_len(17).err(), Some(KuznechikError::InvalidTagLength));
+}
+
+#[test]
+fn try_encrypt_short_gamma() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let gamma = vec![0x12, 0x34, 0x56, 0x78];
+
+    // Act
+    let cbc = AlgCbc::new(&kuz).gamma(gamma.clone()).try_encrypt(DATA.to_vec());
+    let cfb = AlgCfb::new(&kuz).gamma(gamma.clone()).try_encrypt(DATA.to_vec());
+    let ofb = AlgOfb::new(&kuz).gamma(gamma.clone()).try_encrypt(DATA.to_vec());
+    let ctr = AlgCtr::new(&kuz).gamma(gamma).try_encrypt(DATA.to_vec());
+
+    // Assert
+    assert_eq!(cbc, Err(KuznechikError::InvalidGammaLength));
+    assert_eq!(cfb, Err(KuznechikError::InvalidGammaLength));
+    assert_eq!(ofb, Err(KuznechikError::InvalidGammaLength));
+    assert_eq!(ctr, Err(KuznechikError::InvalidGammaLength));
+}
+
+#[test]
+fn try_decrypt_invalid_data() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let mut alg = AlgEcb::new(&kuz);
+    let enc_data = alg.encrypt(DATA.to_vec());
+
+    // Act
+    let truncated = alg.try_decrypt(enc_data[..enc_data.len() - 1].to_vec());
+    let unpadded = alg.try_decrypt(enc_data[..64].to_vec());
+    let mac = AlgMac::new(&kuz).try_decrypt(DATA.to_vec());
+
+    // Assert
+    assert_eq!(truncated, Err(KuznechikError::InvalidDataLength));
+    assert_eq!(unpadded, Err(KuznechikError::InvalidPadding));
+    assert_eq!(mac, Err(KuznechikError::UnsupportedOperation));
+}
+
+#[test]
+fn encrypt_decrypt_partial_block() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let gamma = DATA[..32].to_vec();
+    let data = DATA[..13].to_vec();
+
+    let mut alg = AlgCbc::new(&kuz).gamma(gamma.clone());
+
+    // Act
+    let enc_data = alg.encrypt(data.clone());
+    alg.set_gamma(gamma);
+    let dec_data = alg.decrypt(enc_data.clone());
+
+    let mac = AlgMac::new(&kuz).encrypt(data.clone());
+    let mac_empty = AlgMac::new(&kuz).encrypt(vec![]);
+
+    // Assert
+    assert_eq!(enc_data.len(), 16);
+    assert_eq!(dec_data, data);
+    assert_eq!(mac.len(), 8);
+    assert_eq!(mac_empty.len(), 8);
+}
+
+#[test]
+fn encrypt_alg_ctr_counter_overflow() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let gamma = vec![0xff; 16];
+    let mut alg = AlgCtr::new(&kuz).gamma(gamma.clone());
+
+    // Act
+    let enc_data = alg.encrypt(DATA.to_vec());
+    alg.set_gamma(gamma);
+    let dec_data = alg.decrypt(enc_data);
+
+    // Assert
+    assert_eq!(dec_data, DATA);
+}
+
+fn stream_encrypt<M: StreamMode>(mode: M, data: &[u8], chunk: usize) -> Vec<u8> {
+    let mut enc = Encryptor::new(mode).unwrap();
+    let mut out = Vec::new();
+    for part in data.chunks(chunk) {
+        enc.update(part, &mut out).unwrap();
+    }
+    enc.finalize(&mut out).unwrap();
+    out
+}
+
+fn stream_decrypt<M: StreamMode>(mode: M, data: &[u8], chunk: usize) -> Result<Vec<u8>, KuznechikError> {
+    let mut dec = Decryptor::new(mode)?;
+    let mut out = Vec::new();
+    for part in data.chunks(chunk) {
+        dec.update(part, &mut out)?;
+    }
+    dec.finalize(&mut out)?;
+    Ok(out)
+}
+
+macro_rules! check_stream {
+    ($alg:ident, $kuz:expr, $gamma:expr, $data:expr, $chunk:expr) => {{
+        let expected = $alg::new($kuz).gamma($gamma.clone()).encrypt($data.clone());
+        let enc_data = stream_encrypt($alg::new($kuz).gamma($gamma.clone()), &$data, $chunk);
+        let dec_data = stream_decrypt($alg::new($kuz).gamma($gamma.clone()), &enc_data, $chunk);
+
+        assert_eq!(enc_data, expected);
+        assert_eq!(dec_data, Ok($data.clone()));
+    }};
+}
+
+#[test]
+fn stream_matches_one_shot() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let gamma = DATA[..32].to_vec();
+    let gamma_ctr = DATA[..16].to_vec();
+    let gamma_acpkm = DATA[..8].to_vec();
+
+    for len in [0, 1, 15, 16, 17, 63, 64] {
+        let data = DATA[..len].to_vec();
+        for chunk in [1, 5, 16, 33, 64] {
+            // Act & Assert
+            check_stream!(AlgEcb, &kuz, gamma, data, chunk);
+            check_stream!(AlgCbc, &kuz, gamma, data, chunk);
+            check_stream!(AlgCfb, &kuz, gamma, data, chunk);
+            check_stream!(AlgOfb, &kuz, gamma, data, chunk);
+            check_stream!(AlgCtr, &kuz, gamma_ctr, data, chunk);
+            check_stream!(AlgCtrAcpkm, &kuz, gamma_acpkm, data, chunk);
+        }
+    }
+}
+
+#[test]
+fn stream_decrypt_truncated() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let gamma = DATA[..32].to_vec();
+    let enc_data = AlgCbc::new(&kuz).gamma(gamma.clone()).encrypt(DATA.to_vec());
+
+    // Act
+    let truncated = stream_decrypt(AlgCbc::new(&kuz).gamma(gamma.clone()), &enc_data[..70], 16);
+    let short_gamma = Encryptor::new(AlgCbc::new(&kuz).gamma(gamma[..8].to_vec())).err();
+
+    // Assert
+    assert_eq!(truncated, Err(KuznechikError::InvalidDataLength));
+    assert_eq!(short_gamma, Some(KuznechikError::InvalidGammaLength));
+}
+
+#[cfg(feature = "std")]
+struct FailingWriter;
+
+#[cfg(feature = "std")]
+impl std::io::Write for FailingWriter {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("disk full"))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn io_writer_reader() {
+    use std::io::{Read, Write};
+
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let gamma = DATA[..32].to_vec();
+    let data: Vec<u8> = DATA.iter().cycle().take(20000).copied().collect();
+
+    let expected = AlgCbc::new(&kuz).gamma(gamma.clone()).encrypt(data.clone());
+
+    // Act
+    let mut writer = EncryptWriter::new(Vec::new(), AlgCbc::new(&kuz).gamma(gamma.clone())).unwrap();
+    for part in data.chunks(1000) {
+        writer.write_all(part).unwrap();
+    }
+    let enc_data = writer.finish().unwrap();
+
+    let mut reader = DecryptReader::new(&enc_data[..], AlgCbc::new(&kuz).gamma(gamma.clone())).unwrap();
+    let mut dec_data = Vec::new();
+    reader.read_to_end(&mut dec_data).unwrap();
+
+    let mut reader = DecryptReader::new(&enc_data[..enc_data.len() - 3], AlgCbc::new(&kuz).gamma(gamma)).unwrap();
+    let truncated = reader.read_to_end(&mut Vec::new());
+
+    // Assert
+    assert_eq!(enc_data, expected);
+    assert_eq!(dec_data, data);
+    assert_eq!(truncated.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn io_writer_propagates_errors() {
+    use std::io::Write;
+
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let mut writer = EncryptWriter::new(FailingWriter, AlgCtr::new(&kuz).gamma(DATA[..16].to_vec())).unwrap();
+
+    // Act
+    let result = writer.write_all(&DATA);
+
+    // Assert
+    assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::Other);
+}
+
+/// Отказывает только при первой записи / Fails on the first write only
+#[cfg(feature = "std")]
+struct FlakyWriter {
+    failed: bool,
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for FlakyWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.failed {
+            self.failed = true;
+            return Err(std::io::Error::other("disk full"));
+        }
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn io_writer_poisoned_after_error() {
+    use std::io::Write;
+
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let inner = FlakyWriter { failed: false, data: Vec::new() };
+    let mut writer = EncryptWriter::new(inner, AlgCtr::new(&kuz).gamma(DATA[..16].to_vec())).unwrap();
+
+    // Act
+    let first = writer.write_all(&DATA);
+    let retry = writer.write_all(&DATA);
+    let finish = writer.finish();
+
+    // Assert
+    assert!(first.is_err());
+    assert!(retry.is_err());
+    assert!(finish.is_err());
+}
+
+const MAGMA_KEY: [u8; 32] = [
+    0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00,
+    0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff,
+];
+
+const MAGMA_DATA: [u8; 32] = [
+    0x92, 0xde, 0xf0, 0x6b, 0x3c, 0x13, 0x0a, 0x59, 0xdb, 0x54, 0xc7, 0x04, 0xf8, 0x18, 0x9d, 0x20,
+    0x4a, 0x98, 0xfb, 0x2e, 0x67, 0xa8, 0x02, 0x4c, 0x89, 0x12, 0x40, 0x9b, 0x17, 0xb5, 0x7e, 0x41,
+];
+
+const MAGMA_GAMMA: [u8; 24] = [
+    0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef, 0x23, 0x45, 0x67, 0x89, 0x0a, 0xbc, 0xde, 0xf1,
+    0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef, 0x12,
+];
+
+macro_rules! check_magma {
+    ($alg:ident, $gamma:expr, $expected:expr) => {{
+        let magma = MagmaKeyStore::with_master_key(MAGMA_KEY);
+        let mut alg = $alg::new(&magma).gamma($gamma.to_vec());
+
+        let enc_data = alg.encrypt(MAGMA_DATA.to_vec());
+        alg.set_gamma($gamma.to_vec());
+        let dec_data = alg.decrypt(enc_data.clone());
+
+        assert_eq!(enc_data[..32], $expected);
+        assert_eq!(dec_data, MAGMA_DATA);
+    }};
+}
+
+#[test]
+fn encrypt_decrypt_magma_ecb() {
+    check_magma!(AlgEcb, [], [
+        0x2b, 0x07, 0x3f, 0x04, 0x94, 0xf3, 0x72, 0xa0, 0xde, 0x70, 0xe7, 0x15, 0xd3, 0x55, 0x6e, 0x48,
+        0x11, 0xd8, 0xd9, 0xe9, 0xea, 0xcf, 0xbc, 0x1e, 0x7c, 0x68, 0x26, 0x09, 0x96, 0xc6, 0x7e, 0xfb,
+    ]);
+}
+
+#[test]
+fn encrypt_decrypt_magma_ctr() {
+    check_magma!(AlgCtr, [0x12, 0x34, 0x56, 0x78, 0x00, 0x00, 0x00, 0x00], [
+        0x4e, 0x98, 0x11, 0x0c, 0x97, 0xb7, 0xb9, 0x3c, 0x3e, 0x25, 0x0d, 0x93, 0xd6, 0xe8, 0x5d, 0x69,
+        0x13, 0x6d, 0x86, 0x88, 0x07, 0xb2, 0xdb, 0xef, 0x56, 0x8e, 0xb6, 0x80, 0xab, 0x52, 0xa1, 0x2d,
+    ]);
+}
+
+#[test]
+fn encrypt_decrypt_magma_ofb() {
+    check_magma!(AlgOfb, MAGMA_GAMMA[..16], [
+        0xdb, 0x37, 0xe0, 0xe2, 0x66, 0x90, 0x3c, 0x83, 0x0d, 0x46, 0x64, 0x4c, 0x1f, 0x9a, 0x08, 0x9c,
+        0xa0, 0xf8, 0x30, 0x62, 0x43, 0x0e, 0x32, 0x7e, 0xc8, 0x24, 0xef, 0xb8, 0xbd, 0x4f, 0xdb, 0x05,
+    ]);
+}
+
+#[test]
+fn encrypt_decrypt_magma_cbc() {
+    check_magma!(AlgCbc, MAGMA_GAMMA, [
+        0x96, 0xd1, 0xb0, 0x5e, 0xea, 0x68, 0x39, 0x19, 0xaf, 0xf7, 0x61, 0x29, 0xab, 0xb9, 0x37, 0xb9,
+        0x50, 0x58, 0xb4, 0xa1, 0xc4, 0xbc, 0x00, 0x19, 0x20, 0xb7, 0x8b, 0x1a, 0x7c, 0xd7, 0xe6, 0x67,
+    ]);
+}
+
+#[test]
+fn encrypt_decrypt_magma_cfb() {
+    check_magma!(AlgCfb, MAGMA_GAMMA[..16], [
+        0xdb, 0x37, 0xe0, 0xe2, 0x66, 0x90, 0x3c, 0x83, 0x0d, 0x46, 0x64, 0x4c, 0x1f, 0x9a, 0x08, 0x9c,
+        0x24, 0xbd, 0xd2, 0x03, 0x53, 0x15, 0xd3, 0x8b, 0xbc, 0xc0, 0x32, 0x14, 0x21, 0x07, 0x55, 0x05,
+    ]);
+}
+
+#[test]
+fn encrypt_magma_mac() {
+    // Assign
+    let magma = MagmaKeyStore::with_master_key(MAGMA_KEY);
+    let mut alg = AlgMac::new(&magma);
+
+    // Act
+    let enc_data = alg.encrypt(MAGMA_DATA.to_vec());
+
+    // Assert
+    assert_eq!(enc_data, vec![0x15, 0x4e, 0x72, 0x10]);
+}
+
+/// Внешний шифр, делегирующий "Кузнечику" и считающий вызовы
+struct CountingCipher {
+    kuz: KeyStore,
+    calls: std::cell::Cell<usize>,
+}
+
+impl BlockCipher for CountingCipher {
+    type Block = [u8; 16];
+    const BLOCK_SIZE: usize = 16;
+
+    fn encrypt_block(&self, block: &mut [u8; 16]) {
+        self.calls.set(self.calls.get() + 1);
+        self.kuz.encrypt_block(block);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8; 16]) {
+        self.calls.set(self.calls.get() + 1);
+        self.kuz.decrypt_block(block);
+    }
+}
+
+#[test]
+fn encrypt_decrypt_custom_cipher() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let custom = CountingCipher { kuz: KeyStore::with_password("Kuznechik"), calls: Default::default() };
+    let gamma = DATA[..32].to_vec();
+
+    // Act
+    let enc_data = AlgCbc::new(&custom).gamma(gamma.clone()).encrypt(DATA.to_vec());
+    let dec_data = AlgCbc::new(&custom).gamma(gamma.clone()).decrypt(enc_data.clone());
+    let mac = AlgMac::new(&custom).encrypt(DATA.to_vec());
+
+    // Assert
+    assert_eq!(enc_data, AlgCbc::new(&kuz).gamma(gamma).encrypt(DATA.to_vec()));
+    assert_eq!(dec_data, DATA);
+    assert_eq!(mac, AlgMac::new(&kuz).encrypt(DATA.to_vec()));
+    assert_eq!(custom.calls.get(), 5 + 5 + 1 + 4);
+}
+
+const MGM_NONCE: [u8; 16] = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x00, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88];
+
+const MGM_AD: [u8; 41] = [
+    0x02, 0x02, 0x02, 0x02, 0x02, 0x02, 0x02, 0x02, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+    0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x03, 0x03, 0x03, 0x03, 0x03, 0x03, 0x03, 0x03,
+    0xea, 0x05, 0x05, 0x05, 0x05, 0x05, 0x05, 0x05, 0x05,
+];
+
+#[test]
+fn seal_open_alg_mgm() {
+    // Assign
+    let master_key = [
+        0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+        0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+    ];
+
+    let mut data = DATA.to_vec();
+    data.extend_from_slice(&[0xaa, 0xbb, 0xcc]);
+
+    let expected_enc_data = vec![
+        0xa9, 0x75, 0x7b, 0x81, 0x47, 0x95, 0x6e, 0x90, 0x55, 0xb8, 0xa3, 0x3d, 0xe8, 0x9f, 0x42, 0xfc,
+        0x80, 0x75, 0xd2, 0x21, 0x2b, 0xf9, 0xfd, 0x5b, 0xd3, 0xf7, 0x06, 0x9a, 0xad, 0xc1, 0x6b, 0x39,
+        0x49, 0x7a, 0xb1, 0x59, 0x15, 0xa6, 0xba, 0x85, 0x93, 0x6b, 0x5d, 0x0e, 0xa9, 0xf6, 0x85, 0x1c,
+        0xc6, 0x0c, 0x14, 0xd4, 0xd3, 0xf8, 0x83, 0xd0, 0xab, 0x94, 0x42, 0x06, 0x95, 0xc7, 0x6d, 0xeb,
+        0x2c, 0x75, 0x52,
+        // tag
+        0xcf, 0x5d, 0x65, 0x6f, 0x40, 0xc3, 0x4f, 0x5c, 0x46, 0xe8, 0xbb, 0x0e, 0x29, 0xfc, 0xdb, 0x4c,
+    ];
+
+    let kuz = KeyStore::with_master_key(master_key);
+    let mgm = AlgMgm::new(&kuz);
+
+    // Act
+    let enc_data = mgm.seal(&MGM_NONCE, &MGM_AD, data.clone()).unwrap();
+    let dec_data = mgm.open(&MGM_NONCE, &MGM_AD, enc_data.clone());
+
+    // Assert
+    assert_eq!(enc_data, expected_enc_data);
+    assert_eq!(dec_data, Ok(data));
+}
+
+#[test]
+fn open_alg_mgm_tampered() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let magma = MagmaKeyStore::with_password("Kuznechik");
+    let mgm = AlgMgm::new(&kuz).tag_len(8).unwrap();
+
+    let enc_data = mgm.seal(&MGM_NONCE, &MGM_AD, DATA.to_vec()).unwrap();
+    let mut tampered = enc_data.clone();
+    tampered[3] ^= 0x01;
+
+    let magma_mgm = AlgMgm::new(&magma);
+    let magma_enc = magma_mgm.seal(&MGM_NONCE[..8], &MGM_AD, DATA.to_vec()).unwrap();
+
+    // Act & Assert
+    assert_eq!(enc_data.len(), DATA.len() + 8);
+    assert_eq!(mgm.open(&MGM_NONCE, &MGM_AD, tampered), Err(KuznechikError::AuthenticationFailed));
+    assert_eq!(mgm.open(&MGM_NONCE, &MGM_AD[1..], enc_data.clone()), Err(KuznechikError::AuthenticationFailed));
+    assert_eq!(mgm.open(&MGM_NONCE, &MGM_AD, enc_data[..5].to_vec()), Err(KuznechikError::AuthenticationFailed));
+    assert_eq!(mgm.seal(&[0x80; 16], &MGM_AD, DATA.to_vec()), Err(KuznechikError::InvalidNonce));
+    assert_eq!(AlgMgm::new(&kuz).tag_len(2).err(), Some(KuznechikError::InvalidTagLength));
+    assert_eq!(magma_mgm.open(&MGM_NONCE[..8], &MGM_AD, magma_enc), Ok(DATA.to_vec()));
+}
+
+const CTR_OMAC_IV: [u8; 8] = [0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xce, 0xf0];
+
+/// Ключ K_label, вычисленный вручную / K_label computed by hand
+fn ctr_omac_subkey(kuz: &KeyStore, label: u8) -> KeyStore {
+    let mut master_key = [0u8; 32];
+    for i in 0..2 {
+        let mut block = [0u8; 16];
+        block[0] = label;
+        block[15] = i as u8 + 1;
+        kuz.encrypt_block(&mut block);
+        master_key[16 * i..16 * (i + 1)].copy_from_slice(&block);
+    }
+    kuz.rekeyed(master_key)
+}
+
+#[test]
+fn seal_open_ctr_omac() {
+    // Assign
+    let kuz = KeyStore::with_backend(Backend::Reference).password("Kuznechik");
+    let enc_key = ctr_omac_subkey(&kuz, 0x01);
+    let mac_key = ctr_omac_subkey(&kuz, 0x02);
+
+    let mut counter = CTR_OMAC_IV.to_vec();
+    counter.extend_from_slice(&[0u8; 8]);
+    let expected_cipher_text = AlgCtr::new(&enc_key).gamma(counter).encrypt(DATA[..37].to_vec());
+
+    let mut mac_input = CTR_OMAC_IV.to_vec();
+    mac_input.extend_from_slice(&MGM_AD);
+    mac_input.extend_from_slice(&expected_cipher_text);
+    mac_input.extend_from_slice(&(MGM_AD.len() as u64).to_be_bytes());
+    mac_input.extend_from_slice(&37u64.to_be_bytes());
+    let expected_tag = AlgMac::new(&mac_key).tag_len(16).unwrap().encrypt(mac_input);
+
+    let aead = CtrOmac::new(&kuz);
+
+    // Act
+    let enc_data = aead.seal(&CTR_OMAC_IV, &MGM_AD, DATA[..37].to_vec()).unwrap();
+    let dec_data = aead.open(&CTR_OMAC_IV, &MGM_AD, enc_data.clone());
+
+    let mut detached = DATA[..37].to_vec();
+    let tag = aead.encrypt_in_place_detached(&CTR_OMAC_IV, &MGM_AD, &mut detached).unwrap();
+
+    // Assert
+    assert_eq!(enc_key.get_backend(), Backend::Reference);
+    assert_eq!(&enc_data[..37], &expected_cipher_text[..]);
+    assert_eq!(&enc_data[37..], &expected_tag[..]);
+    assert_ne!(expected_cipher_text, AlgCtr::new(&kuz).gamma([&CTR_OMAC_IV[..], &[0u8; 8]].concat()).encrypt(DATA[..37].to_vec()));
+    assert_eq!(dec_data, Ok(DATA[..37].to_vec()));
+    assert_eq!(detached, expected_cipher_text);
+    assert_eq!(tag, expected_tag);
+    assert_eq!(aead.decrypt_in_place_detached(&CTR_OMAC_IV, &MGM_AD, &mut detached, &tag), Ok(()));
+    assert_eq!(detached, DATA[..37].to_vec());
+
+    for len in [0, 1, 8, 64] {
+        let magma = MagmaKeyStore::with_password("Kuznechik");
+        let magma_aead = CtrOmac::new(&magma);
+        let sealed = magma_aead.seal(&CTR_OMAC_IV[..4], &[], DATA[..len].to_vec()).unwrap();
+        assert_eq!(sealed.len(), len + 8);
+        assert_eq!(magma_aead.open(&CTR_OMAC_IV[..4], &[], sealed), Ok(DATA[..len].to_vec()));
+    }
+}
+
+#[test]
+fn open_ctr_omac_tampered() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let aead = CtrOmac::new(&kuz).tag_len(8).unwrap();
+    let enc_data = aead.seal(&CTR_OMAC_IV, &MGM_AD, DATA.to_vec()).unwrap();
+
+    let mut other_iv = CTR_OMAC_IV;
+    other_iv[7] ^= 0x01;
+
+    // Act & Assert
+    assert_eq!(enc_data.len(), DATA.len() + 8);
+    for i in 0..enc_data.len() {
+        let mut tampered = enc_data.clone();
+        tampered[i] ^= 0x01;
+        assert_eq!(aead.open(&CTR_OMAC_IV, &MGM_AD, tampered), Err(KuznechikError::AuthenticationFailed), "byte {}", i);
+    }
+    assert_eq!(aead.open(&other_iv, &MGM_AD, enc_data.clone()), Err(KuznechikError::AuthenticationFailed));
+    assert_eq!(aead.open(&CTR_OMAC_IV, &MGM_AD[1..], enc_data.clone()), Err(KuznechikError::AuthenticationFailed));
+    assert_eq!(aead.open(&CTR_OMAC_IV, &MGM_AD, enc_data[..5].to_vec()), Err(KuznechikError::AuthenticationFailed));
+    assert_eq!(CtrOmac::new(&KeyStore::with_password("Kuznechik!")).tag_len(8).unwrap().open(&CTR_OMAC_IV, &MGM_AD, enc_data.clone()),
+               Err(KuznechikError::AuthenticationFailed));
+
+    let mut cipher_text = enc_data[..DATA.len()].to_vec();
+    let result = aead.decrypt_in_place_detached(&CTR_OMAC_IV, &[], &mut cipher_text, &enc_data[DATA.len()..]);
+    assert_eq!(result, Err(KuznechikError::AuthenticationFailed));
+    assert_eq!(cipher_text, enc_data[..DATA.len()].to_vec());
+
+    assert_eq!(aead.seal(&[0u8; 16], &MGM_AD, DATA.to_vec()), Err(KuznechikError::InvalidGammaLength));
+    assert_eq!(CtrOmac::new(&kuz).tag_len(3).err(), Some(KuznechikError::InvalidTagLength));
+    assert_eq!(CtrOmac::new(&kuz).tag_len(17).err(), Some(KuznechikError::InvalidTagLength));
+}
+
+#[test]
+fn encrypt_decrypt_alg_ctr_acpkm() {
+    // Assign
+    let gamma = vec![0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xce, 0xf0];
+
+    let master_key = [
+        0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+        0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+    ];
+
+    // RFC 8645, A.1: 7 блоков, N = 256 бит / 7 blocks, N = 256 bits
+    let mut data = DATA.to_vec();
+    data.extend_from_slice(&[
+        0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xee, 0xff, 0x0a, 0x00, 0x11, 0x22,
+        0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xee, 0xff, 0x0a, 0x00, 0x11, 0x22, 0x33,
+        0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xee, 0xff, 0x0a, 0x00, 0x11, 0x22, 0x33, 0x44,
+    ]);
+
+    // Первая секция совпадает с CTR, каждая следующая зашифрована на новом ключе ACPKM(K)
+    let expected_enc_data = vec![
+        0xf1, 0x95, 0xd8, 0xbe, 0xc1, 0x0e, 0xd1, 0xdb, 0xd5, 0x7b, 0x5f, 0xa2, 0x40, 0xbd, 0xa1, 0xb8,
+        0x85, 0xee, 0xe7, 0x33, 0xf6, 0xa1, 0x3e, 0x5d, 0xf3, 0x3c, 0xe4, 0xb3, 0x3c, 0x45, 0xde, 0xe4,
+        0x4b, 0xce, 0xeb, 0x8f, 0x64, 0x6f, 0x4c, 0x55, 0x00, 0x17, 0x06, 0x27, 0x5e, 0x85, 0xe8, 0x00,
+        0x58, 0x7c, 0x4d, 0xf5, 0x68, 0xd0, 0x94, 0x39, 0x3e, 0x48, 0x34, 0xaf, 0xd0, 0x80, 0x50, 0x46,
+        0xcf, 0x30, 0xf5, 0x76, 0x86, 0xae, 0xec, 0xe1, 0x1c, 0xfc, 0x6c, 0x31, 0x6b, 0x8a, 0x89, 0x6e,
+        0xdf, 0xfd, 0x07, 0xec, 0x81, 0x36, 0x36, 0x46, 0x0c, 0x4f, 0x3b, 0x74, 0x34, 0x23, 0x16, 0x3e,
+        0x64, 0x09, 0xa9, 0xc2, 0x82, 0xfa, 0xc8, 0xd4, 0x69, 0xd2, 0x21, 0xe7, 0xfb, 0xd6, 0xde, 0x5d,
+    ];
+
+    let kuz = KeyStore::with_master_key(master_key);
+    let mut alg = AlgCtrAcpkm::new(&kuz).section_size(32).unwrap().gamma(gamma.clone());
+
+    // Act
+    let enc_data = alg.encrypt(data.clone());
+    alg.set_gamma(gamma);
+    let dec_data = alg.decrypt(enc_data.clone());
+
+    // Assert
+    assert_eq!(enc_data, expected_enc_data);
+    assert_eq!(dec_data, data);
+}
+
+#[test]
+fn acpkm_section_key() {
+    // Assign
+    let master_key = [
+        0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+        0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+    ];
+    let expected_key = [
+        0x26, 0x66, 0xed, 0x40, 0xae, 0x68, 0x78, 0x11, 0x74, 0x5c, 0xa0, 0xb4, 0x48, 0xf5, 0x7a, 0x7b,
+        0x39, 0x0a, 0xdb, 0x57, 0x80, 0x30, 0x7e, 0x8e, 0x96, 0x59, 0xac, 0x40, 0x3a, 0xe6, 0x0c, 0x60,
+    ];
+
+    let kuz = KeyStore::with_master_key(master_key);
+
+    // Act
+    let next = acpkm(&kuz);
+
+    // Assert
+    assert!(next.keys == KeyStore::with_master_key(expected_key).keys);
+}
+
+#[test]
+fn backends_match_default() {
+    // Assign
+    let master_key = [
+        0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+        0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+    ];
+    let gamma = DATA[..32].to_vec();
+    let half = gamma[..8].to_vec();
+    let data: Vec<u8> = DATA.iter().cycle().take(1000).copied().collect();
+
+    // Режимы на реализации по умолчанию, сверенной с векторами выше / Modes on the default backend, checked against the vectors above
+    let outputs = |kuz: &KeyStore| vec![
+        AlgEcb::new(kuz).encrypt(data.clone()),
+        AlgCtr::new(kuz).gamma(gamma[..16].to_vec()).encrypt(data.clone()),
+        AlgOfb::new(kuz).gamma(gamma.clone()).encrypt(data.clone()),
+        AlgCbc::new(kuz).gamma(gamma.clone()).encrypt(data.clone()),
+        AlgCfb::new(kuz).gamma(gamma.clone()).encrypt(data.clone()),
+        AlgMac::new(kuz).encrypt(data.clone()),
+        AlgMgm::new(kuz).seal(&MGM_NONCE, &MGM_AD, data.clone()).unwrap(),
+        AlgCtrAcpkm::new(kuz).section_size(32).unwrap().gamma(half.clone()).encrypt(data.clone()),
+        AlgMacAcpkm::new(kuz).section_size(32).unwrap().encrypt(data.clone()),
+    ];
+    let kuz = KeyStore::with_master_key(master_key);
+    let expected = outputs(&kuz);
+
+    for backend in BACKENDS {
+        let kuz = KeyStore::with_backend(backend).master_key(master_key);
+
+        // Act
+        let actual = outputs(&kuz);
+        let decrypted = AlgCbc::new(&kuz).gamma(gamma.clone()).decrypt(expected[3].clone());
+        let next = acpkm(&kuz);
+
+        // Assert
+        assert_eq!(actual, expected, "{:?}", backend);
+        assert_eq!(decrypted, data, "{:?}", backend);
+        assert!(next.keys == acpkm(&KeyStore::with_master_key(master_key)).keys, "{:?}", backend);
+        assert_eq!(next.get_backend(), backend);
+        assert_eq!(acpkm(&next).get_backend(), backend);
+    }
+}
+
+/// Шифр с настройкой, которую `rekeyed` должен переносить на новые ключи / A cipher whose setting must survive `rekeyed`
+struct SettingCipher {
+    kuz: KeyStore,
+    setting: u8,
+}
+
+impl BlockCipher for SettingCipher {
+    type Block = [u8; 16];
+    const BLOCK_SIZE: usize = 16;
+
+    fn encrypt_block(&self, block: &mut [u8; 16]) {
+        assert_eq!(self.setting, 7, "section key lost the cipher settings");
+        self.kuz.encrypt_block(block);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8; 16]) {
+        assert_eq!(self.setting, 7, "section key lost the cipher settings");
+        self.kuz.decrypt_block(block);
+    }
+}
+
+impl MasterKeyInit for SettingCipher {
+    fn from_master_key(master_key: [u8; 32]) -> Self {
+        SettingCipher { kuz: KeyStore::with_master_key(master_key), setting: 0 }
+    }
+
+    fn rekeyed(&self, master_key: [u8; 32]) -> Self {
+        SettingCipher { kuz: KeyStore::with_master_key(master_key), setting: self.setting }
+    }
+}
+
+#[test]
+fn acpkm_section_keys_keep_settings() {
+    // Assign
+    let cipher = SettingCipher { kuz: KeyStore::with_password("Kuznechik"), setting: 7 };
+    let kuz = KeyStore::with_password("Kuznechik");
+    let gamma = vec![0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xce, 0xf0];
+
+    // Act
+    let ctr = AlgCtrAcpkm::new(&cipher).section_size(32).unwrap().gamma(gamma.clone()).encrypt(DATA.to_vec());
+    let mac = AlgMacAcpkm::new(&cipher).section_size(32).unwrap().encrypt(DATA.to_vec());
+
+    // Assert
+    assert_eq!(ctr, AlgCtrAcpkm::new(&kuz).section_size(32).unwrap().gamma(gamma).encrypt(DATA.to_vec()));
+    assert_eq!(mac, AlgMacAcpkm::new(&kuz).section_size(32).unwrap().encrypt(DATA.to_vec()));
+}
+
+#[test]
+fn alg_ctr_acpkm_invalid_section_size() {
+    // Assign
+    let kuz = KeyStore::new();
+
+    // Act
+    let zero = AlgCtrAcpkm::new(&kuz).section_size(0).err();
+    let unaligned = AlgCtrAcpkm::new(&kuz).section_size(24).err();
+
+    // Assert
+    assert_eq!(zero, Some(KuznechikError::InvalidSectionSize));
+    assert_eq!(unaligned, Some(KuznechikError::InvalidSectionSize));
+}
+
+#[test]
+fn encrypt_alg_mac_acpkm() {
+    // Assign
+    let master_key = [
+        0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+        0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+    ];
+
+    let mut data = DATA.to_vec();
+    data.extend_from_slice(&[
+        0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xee, 0xff, 0x0a, 0x00, 0x11, 0x22,
+    ]);
+
+    let expected_mac = vec![
+        0xfb, 0xb8, 0xdc, 0xee, 0x45, 0xbe, 0xa6, 0x7c, 0x35, 0xf5, 0x8c, 0x57, 0x00, 0x89, 0x8e, 0x5d,
+    ];
+
+    let kuz = KeyStore::with_master_key(master_key);
+    let mut alg = AlgMacAcpkm::new(&kuz)
+        .section_size(32).unwrap()
+        .master_section_size(96).unwrap();
+
+    // Act
+    let mac = alg.encrypt(data);
+
+    // Assert
+    assert_eq!(mac, expected_mac);
+}
+
+/// OMAC-ACPKM по определению RFC 8645: ключи секций и K1 - гамма ACPKM-Master, дополнение по процедуре 3,
+/// для неполного последнего блока K2 = K1 << 1 ^ B_n / OMAC-ACPKM computed step by step from the derived section keys
+fn mac_acpkm_reference(kuz: &KeyStore, data: &[u8], section_size: usize, master_section_size: usize) -> Vec<u8> {
+    let mut padded = data.to_vec();
+    let is_partial = padded.is_empty() || !padded.len().is_multiple_of(16);
+    if is_partial {
+        padded.push(0x80);
+        padded.resize(padded.len().next_multiple_of(16), 0);
+    }
+
+    let sections = padded.len().div_ceil(section_size);
+    let material = AlgCtrAcpkm::new(kuz)
+        .section_size(master_section_size).unwrap()
+        .gamma(vec![0xff; 8])
+        .encrypt(vec![0; sections * 48]);
+
+    let count_blocks = padded.len() / 16;
+    let mut state = [0u8; 16];
+    for (i, block) in padded.chunks(16).enumerate() {
+        let section = &material[i * 16 / section_size * 48..][..48];
+        let section_key = KeyStore::with_master_key(section[..32].try_into().unwrap());
+
+        state.iter_mut().zip(block).for_each(|(s, b)| *s ^= b);
+        if i == count_blocks - 1 {
+            let mut k: [u8; 16] = section[32..].try_into().unwrap();
+            if is_partial {
+                let msb = k[0] >> 7;
+                for j in 0..15 {
+                    k[j] = (k[j] << 1) | (k[j + 1] >> 7);
+                }
+                k[15] = (k[15] << 1) ^ (0x87 * msb);
+            }
+            state.iter_mut().zip(k).for_each(|(s, k)| *s ^= k);
+        }
+        section_key.encrypt_block(&mut state);
+    }
+    state.to_vec()
+}
+
+#[test]
+fn alg_mac_acpkm_partial_block() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let mut alg = AlgMacAcpkm::new(&kuz)
+        .section_size(32).unwrap()
+        .master_section_size(96).unwrap();
+
+    // Act
+    let macs: Vec<_> = [0, 1, 15, 16, 17, 33, 64]
+        .iter()
+        .map(|&len| alg.encrypt(DATA[..len].to_vec()))
+        .collect();
+
+    // Assert
+    for (i, mac) in macs.iter().enumerate() {
+        assert_eq!(mac.len(), 16);
+        assert!(macs[i + 1..].iter().all(|other| other != mac));
+    }
+    assert_eq!(alg.section_size(24).err(), Some(KuznechikError::InvalidSectionSize));
+}
+
+#[test]
+fn alg_mac_acpkm_matches_reference() {
+    // Assign
+    let master_key = [
+        0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+        0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+    ];
+    let kuz = KeyStore::with_master_key(master_key);
+
+    let mut data = DATA.to_vec();
+    data.extend_from_slice(&[
+        0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xee, 0xff, 0x0a, 0x00, 0x11, 0x22,
+    ]);
+
+    // Пример RFC 8645 проверяет и сам эталон / The RFC 8645 example checks the reference itself
+    assert_eq!(mac_acpkm_reference(&kuz, &data, 32, 96), vec![
+        0xfb, 0xb8, 0xdc, 0xee, 0x45, 0xbe, 0xa6, 0x7c, 0x35, 0xf5, 0x8c, 0x57, 0x00, 0x89, 0x8e, 0x5d,
+    ]);
+
+    // Неполный последний блок в начале новой секции (33, 65), в её середине (47, 71) и без смены секции (1, 17)
+    for len in [0, 1, 17, 31, 33, 47, 65, 71, 79] {
+        for (section_size, master_section_size) in [(32, 96), (48, 96), (16, 48)] {
+            let mut alg = AlgMacAcpkm::new(&kuz)
+                .section_size(section_size).unwrap()
+                .master_section_size(master_section_size).unwrap();
+
+            // Act
+            let mac = alg.encrypt(data[..len].to_vec());
+
+            // Assert
+            assert_eq!(mac, mac_acpkm_reference(&kuz, &data[..len], section_size, master_section_size),
+                       "len {}, section {}", len, section_size);
+        }
+    }
+}
+
+#[test]
+fn batched_matches_sequential() {
+    // Assign
+    // Больше части одного потока с `rayon` (64 КиБ) и не кратно блоку; CountingCipher обрабатывает блоки по одному
+    let data: Vec<u8> = (0..3 * 65536 + 5).map(|i: usize| (i as u8).wrapping_mul(151) ^ (i >> 9) as u8).collect();
+    let gamma = DATA[..48].to_vec();
+
+    for backend in [Backend::Table, Backend::Simd] {
+        let kuz = KeyStore::with_backend(backend).password("Kuznechik");
+        let sequential = CountingCipher { kuz: KeyStore::with_backend(backend).password("Kuznechik"), calls: Default::default() };
+
+        // Act
+        let ecb = AlgEcb::new(&kuz).encrypt(data.clone());
+        let ctr = AlgCtr::new(&kuz).gamma(gamma[..16].to_vec()).encrypt(data.clone());
+        let cbc = AlgCbc::new(&kuz).gamma(gamma.clone()).encrypt(data.clone());
+        let cfb = AlgCfb::new(&kuz).gamma(gamma[..32].to_vec()).encrypt(data.clone());
+
+        // Assert
+        assert!(ecb == AlgEcb::new(&sequential).encrypt(data.clone()));
+        assert!(ctr == AlgCtr::new(&sequential).gamma(gamma[..16].to_vec()).encrypt(data.clone()));
+        assert!(cbc == AlgCbc::new(&sequential).gamma(gamma.clone()).encrypt(data.clone()));
+        assert!(cfb == AlgCfb::new(&sequential).gamma(gamma[..32].to_vec()).encrypt(data.clone()));
+
+        assert!(AlgEcb::new(&kuz).decrypt(ecb) == data);
+        assert!(AlgCtr::new(&kuz).gamma(gamma[..16].to_vec()).decrypt(ctr) == data);
+        assert!(AlgCbc::new(&kuz).gamma(gamma.clone()).decrypt(cbc) == data);
+        assert!(AlgCfb::new(&kuz).gamma(gamma[..32].to_vec()).decrypt(cfb) == data);
+    }
+
+    let magma = MagmaKeyStore::with_password("Magma");
+    let mut expected = data[..data.len() / 8 * 8].to_vec();
+    for block in expected.chunks_exact_mut(8) {
+        magma.encrypt_block(block.try_into().unwrap());
+    }
+    let mut enc_data = AlgEcb::new(&magma).encrypt(data.clone());
+    enc_data.truncate(expected.len());
+    assert!(enc_data == expected);
+}
+
+#[test]
+fn encrypt_decrypt_in_place() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let gamma = DATA[..48].to_vec();
+    let message = &DATA[..37];
+
+    // Act
+    let mut ctr = DATA;
+    AlgCtr::new(&kuz).gamma(gamma[..16].to_vec()).encrypt_in_place(&mut ctr[..37]).unwrap();
+    let mut ofb = DATA;
+    AlgOfb::new(&kuz).gamma(gamma.clone()).encrypt_in_place(&mut ofb[..37]).unwrap();
+    let mut acpkm = DATA;
+    AlgCtrAcpkm::new(&kuz).gamma(gamma[..8].to_vec()).encrypt_in_place(&mut acpkm[..37]).unwrap();
+    let mut cfb = DATA;
+    AlgCfb::new(&kuz).gamma(gamma.clone()).encrypt_in_place(&mut cfb).unwrap();
+    let mut cbc = DATA;
+    AlgCbc::new(&kuz).gamma(gamma.clone()).encrypt_in_place(&mut cbc).unwrap();
+
+    // Assert
+    assert_eq!(ctr[..37], AlgCtr::new(&kuz).gamma(gamma[..16].to_vec()).encrypt(message.to_vec())[..]);
+    assert_eq!(ofb[..37], AlgOfb::new(&kuz).gamma(gamma.clone()).encrypt(message.to_vec())[..]);
+    assert_eq!(acpkm[..37], AlgCtrAcpkm::new(&kuz).gamma(gamma[..8].to_vec()).encrypt(message.to_vec())[..]);
+    assert_eq!(ctr[37..], DATA[37..]);
+
+    // Без дополнения совпадает с первыми блоками обычного шифртекста
+    assert_eq!(cfb[..], AlgCfb::new(&kuz).gamma(gamma.clone()).encrypt(DATA.to_vec())[..64]);
+    assert_eq!(cbc[..], AlgCbc::new(&kuz).gamma(gamma.clone()).encrypt(DATA.to_vec())[..64]);
+
+    AlgCtr::new(&kuz).gamma(gamma[..16].to_vec()).decrypt_in_place(&mut ctr[..37]).unwrap();
+    AlgCfb::new(&kuz).gamma(gamma.clone()).decrypt_in_place(&mut cfb).unwrap();
+    AlgCbc::new(&kuz).gamma(gamma.clone()).decrypt_in_place(&mut cbc).unwrap();
+    assert_eq!(ctr, DATA);
+    assert_eq!(cfb, DATA);
+    assert_eq!(cbc, DATA);
+
+    assert_eq!(AlgCfb::new(&kuz).gamma(gamma.clone()).encrypt_in_place(&mut cfb[..37]), Err(KuznechikError::InvalidDataLength));
+    assert_eq!(AlgCtr::new(&kuz).encrypt_in_place(&mut ctr), Err(KuznechikError::InvalidGammaLength));
+}
+
+#[test]
+fn encrypt_decrypt_padded() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let gamma = DATA[..32].to_vec();
+
+    for msg_len in [0, 37, 48] {
+        let mut buf = [0xffu8; 80];
+        buf[..msg_len].copy_from_slice(&DATA[..msg_len]);
+        let (mut ecb_buf, mut cfb_buf, mut ctr_buf) = (buf, buf, buf);
+
+        // Act
+        let cbc = AlgCbc::new(&kuz).gamma(gamma.clone()).encrypt_padded(&mut buf, msg_len).unwrap().to_vec();
+        let ecb = AlgEcb::new(&kuz).encrypt_padded(&mut ecb_buf, msg_len).unwrap().to_vec();
+        let cfb = AlgCfb::new(&kuz).gamma(gamma.clone()).encrypt_padded(&mut cfb_buf, msg_len).unwrap().to_vec();
+        let ctr = AlgCtr::new(&kuz).gamma(gamma[..16].to_vec()).encrypt_padded(&mut ctr_buf, msg_len).unwrap().to_vec();
+
+        // Assert
+        let message = DATA[..msg_len].to_vec();
+        assert_eq!(cbc, AlgCbc::new(&kuz).gamma(gamma.clone()).encrypt(message.clone()));
+        assert_eq!(ecb, AlgEcb::new(&kuz).encrypt(message.clone()));
+        assert_eq!(cfb, AlgCfb::new(&kuz).gamma(gamma.clone()).encrypt(message.clone()));
+        assert_eq!(ctr, AlgCtr::new(&kuz).gamma(gamma[..16].to_vec()).encrypt(message.clone()));
+
+        let len = cbc.len();
+        assert_eq!(AlgCbc::new(&kuz).gamma(gamma.clone()).decrypt_padded(&mut buf[..len]).unwrap(), &message[..]);
+        assert_eq!(AlgEcb::new(&kuz).decrypt_padded(&mut ecb_buf[..len]).unwrap(), &message[..]);
+        assert_eq!(AlgCfb::new(&kuz).gamma(gamma.clone()).decrypt_padded(&mut cfb_buf[..len]).unwrap(), &message[..]);
+        assert_eq!(AlgCtr::new(&kuz).gamma(gamma[..16].to_vec()).decrypt_padded(&mut ctr_buf[..msg_len]).unwrap(), &message[..]);
+    }
+
+    let mut buf = DATA;
+    assert_eq!(AlgEcb::new(&kuz).encrypt_padded(&mut buf[..48], 48), Err(KuznechikError::BufferTooSmall));
+    assert_eq!(AlgCtr::new(&kuz).gamma(gamma[..16].to_vec()).encrypt_padded(&mut buf[..16], 17), Err(KuznechikError::BufferTooSmall));
+    assert_eq!(AlgEcb::new(&kuz).decrypt_padded(&mut buf[..37]), Err(KuznechikError::InvalidDataLength));
+    assert_eq!(buf, DATA);
+}
+
+#[test]
+#[cfg(feature = "getrandom")]
+fn encrypt_decrypt_random_iv() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let magma = MagmaKeyStore::with_password("Kuznechik");
+    let data = DATA[..37].to_vec();
+
+    // Act
+    let (mut cbc, cbc_iv) = AlgCbc::with_random_iv(&kuz).unwrap();
+    let (mut ctr, ctr_iv) = AlgCtr::with_random_iv(&kuz).unwrap();
+    let (mut acpkm, acpkm_iv) = AlgCtrAcpkm::with_random_iv(&kuz).unwrap();
+    let (mut ofb, ofb_iv) = AlgOfb::with_random_iv(&kuz).unwrap();
+    let (mut cfb, cfb_iv) = AlgCfb::with_random_iv(&kuz).unwrap();
+    let (mut magma_ctr, magma_iv) = AlgCtr::with_random_iv(&magma).unwrap();
+
+    // Assert
+    assert_eq!(cbc_iv.len(), 16);
+    assert_eq!(ctr_iv.len(), 16);
+    assert_eq!(&ctr_iv[8..], &[0u8; 8]);
+    assert_eq!(acpkm_iv.len(), 8);
+    assert_eq!(ofb_iv.len(), 16);
+    assert_eq!(cfb_iv.len(), 16);
+    assert_eq!(magma_iv.len(), 8);
+    assert_eq!(&magma_iv[4..], &[0u8; 4]);
+
+    assert_ne!(cbc_iv, ofb_iv);
+    assert_ne!(AlgCbc::<KeyStore>::random_iv().unwrap(), AlgCbc::<KeyStore>::random_iv().unwrap());
+
+    assert_eq!(AlgCbc::new(&kuz).gamma(cbc_iv).decrypt(cbc.encrypt(data.clone())), data);
+    assert_eq!(AlgCtr::new(&kuz).gamma(ctr_iv).decrypt(ctr.encrypt(data.clone())), data);
+    assert_eq!(AlgCtrAcpkm::new(&kuz).gamma(acpkm_iv).decrypt(acpkm.encrypt(data.clone())), data);
+    assert_eq!(AlgOfb::new(&kuz).gamma(ofb_iv).decrypt(ofb.encrypt(data.clone())), data);
+    assert_eq!(AlgCfb::new(&kuz).gamma(cfb_iv).decrypt(cfb.encrypt(data.clone())), data);
+    assert_eq!(AlgCtr::new(&magma).gamma(magma_iv).decrypt(magma_ctr.encrypt(data.clone())), data);
+}
+
+#[test]
+#[cfg(feature = "getrandom")]
+fn random_nonce_aead() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let mgm = AlgMgm::new(&kuz);
+    let aead = CtrOmac::new(&kuz);
+
+    // Act
+    let nonces: Vec<_> = (0..16).map(|_| mgm.random_nonce().unwrap()).collect();
+    let iv = aead.random_iv().unwrap();
+
+    // Assert
+    assert!(nonces.iter().all(|nonce| nonce.len() == 16 && nonce[0] & 0x80 == 0));
+    assert_ne!(nonces[0], nonces[1]);
+    assert_eq!(iv.len(), 8);
+
+    let sealed = mgm.seal(&nonces[0], &MGM_AD, DATA.to_vec()).unwrap();
+    assert_eq!(mgm.open(&nonces[0], &MGM_AD, sealed), Ok(DATA.to_vec()));
+    let sealed = aead.seal(&iv, &MGM_AD, DATA.to_vec()).unwrap();
+    assert_eq!(aead.open(&iv, &MGM_AD, sealed), Ok(DATA.to_vec()));
+}
@@ -0,0 +1,141 @@
+use std::io::{self, Read, Write};
+
+use kuznechik::{AlgCbc, AlgCfb, AlgCtr, AlgEcb, AlgOfb, DecryptReader, EncryptWriter, KeyStore, Kuznechik,
+    PasswordParams, Prf, StreamMode};
+use zeroize::Zeroize;
+
+use super::key::{self, KeySource};
+use super::{Mode, Result};
+
+/// Длина блока "Кузнечика" / Kuznechik block length
+const BLOCK_LEN: usize = 16;
+
+impl Mode {
+    /// Длина создаваемой синхропосылки: n для CBC, OFB и CFB, n/2 для CTR
+    /// Generated IV length: n for CBC, OFB and CFB, n/2 for CTR
+    pub(crate) fn iv_len(self) -> usize {
+        match self {
+            Mode::Ecb => 0,
+            Mode::Ctr => BLOCK_LEN / 2,
+            Mode::Cbc | Mode::Ofb | Mode::Cfb => BLOCK_LEN,
+        }
+    }
+
+    /// Гамма режима из синхропосылки: для CTR счётчик IV || 0^(n/2)
+    /// Mode gamma from the IV: for CTR the counter IV || 0^(n/2)
+    fn gamma(self, iv: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Mode::Ecb if !iv.is_empty() => Err("ECB does not use an IV".into()),
+            Mode::Ctr if iv.len() != BLOCK_LEN / 2 => Err("CTR needs an IV of 8 bytes (n/2)".into()),
+            Mode::Ctr => Ok([iv, &[0u8; BLOCK_LEN / 2]].concat()),
+            _ => Ok(iv.to_vec()),
+        }
+    }
+}
+
+/// Вызывает `$f(mode, args...)` с режимом `$mode` над ключом `$key` и гаммой `$gamma`
+macro_rules! with_mode {
+    ($mode:expr, $key:expr, $gamma:expr, $f:ident($($arg:expr),*)) => {
+        match $mode {
+            Mode::Ecb => $f(AlgEcb::new($key).try_gamma($gamma)?, $($arg),*),
+            Mode::Cbc => $f(AlgCbc::new($key).try_gamma($gamma)?, $($arg),*),
+            Mode::Ctr => $f(AlgCtr::new($key).try_gamma($gamma)?, $($arg),*),
+            Mode::Ofb => $f(AlgOfb::new($key).try_gamma($gamma)?, $($arg),*),
+            Mode::Cfb => $f(AlgCfb::new($key).try_gamma($gamma)?, $($arg),*),
+        }
+    };
+}
+
+///
+/// Зашифрование потока / Stream encryption
+///
+/// Вывод: параметры PBKDF2 (для пароля) || синхропосылка (если не задана) || шифртекст.
+/// Output: PBKDF2 params (for a password) || IV (unless given) || ciphertext.
+///
+pub(crate) fn encrypt(mode: Mode, key: &KeySource, iv: Option<&[u8]>, iterations: u32,
+                      input: &mut dyn Read, output: &mut dyn Write) -> Result<()> {
+    let key_store = match key {
+        KeySource::Raw(master_key) => KeyStore::with_master_key(*master_key),
+        KeySource::Password(password) => {
+            let params = PasswordParams::new(key::random_bytes(key::SALT_LEN)?, iterations, Prf::HmacStreebog512)?;
+            output.write_all(&params.to_bytes())?;
+            KeyStore::with_password_and_params(password, &params)
+        }
+    };
+
+    let iv = match iv {
+        Some(iv) => iv.to_vec(),
+        None => {
+            let iv = key::random_bytes(mode.iv_len())?;
+            output.write_all(&iv)?;
+            iv
+        }
+    };
+
+    let mut gamma = mode.gamma(&iv)?;
+    let result = with_mode!(mode, &key_store, &gamma, encrypt_with(input, output));
+    gamma.zeroize();
+    result
+}
+
+/// Расшифрование потока в формате [`encrypt`] / Decrypts a stream produced by [`encrypt`]
+pub(crate) fn decrypt(mode: Mode, key: &KeySource, iv: Option<&[u8]>, max_iterations: u32,
+                      input: &mut dyn Read, output: &mut dyn Write) -> Result<()> {
+    let key_store = match key {
+        KeySource::Raw(master_key) => KeyStore::with_master_key(*master_key),
+        KeySource::Password(password) => KeyStore::with_password_and_params(password, &read_params(input, max_iterations)?),
+    };
+
+    let iv = match iv {
+        Some(iv) => iv.to_vec(),
+        None => {
+            let mut iv = vec![0u8; mode.iv_len()];
+            read_header(input, &mut iv)?;
+            iv
+        }
+    };
+
+    let mut gamma = mode.gamma(&iv)?;
+    let result = with_mode!(mode, &key_store, &gamma, decrypt_with(input, output));
+    gamma.zeroize();
+    result
+}
+
+fn encrypt_with<M: StreamMode>(mode: M, input: &mut dyn Read, output: &mut dyn Write) -> Result<()> {
+    let mut writer = EncryptWriter::new(output, mode)?;
+    io::copy(input, &mut writer)?;
+    writer.finish()?;
+    Ok(())
+}
+
+fn decrypt_with<M: StreamMode>(mode: M, input: &mut dyn Read, output: &mut dyn Write) -> Result<()> {
+    let mut reader = DecryptReader::new(input, mode)?;
+    io::copy(&mut reader, output)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// Параметры PBKDF2 в начале потока; число итераций не проверено, поэтому ограничено сверху
+/// PBKDF2 params at the start of the stream; the iteration count is unauthenticated, so it is capped
+fn read_params(input: &mut dyn Read, max_iterations: u32) -> Result<PasswordParams> {
+    let mut header = vec![0u8; 7];
+    read_header(input, &mut header)?;
+
+    let salt_len = u16::from_be_bytes([header[5], header[6]]) as usize;
+    header.resize(7 + salt_len, 0);
+    read_header(input, &mut header[7..])?;
+
+    let (params, _) = PasswordParams::from_bytes(&header)?;
+    if params.iterations() > max_iterations {
+        return Err(format!("the header asks for {} PBKDF2 iterations, more than --max-iterations {}",
+                           params.iterations(), max_iterations).into());
+    }
+    Ok(params)
+}
+
+fn read_header(input: &mut dyn Read, buf: &mut [u8]) -> Result<()> {
+    input.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => "the input is too short for its header".into(),
+        _ => e.into(),
+    })
+}
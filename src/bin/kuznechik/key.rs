@@ -0,0 +1,100 @@
+use std::fs;
+use std::path::Path;
+
+use kuznechik::Block256;
+use zeroize::Zeroize;
+
+use super::Result;
+
+/// Число итераций PBKDF2 по умолчанию / Default PBKDF2 iteration count
+pub(crate) const DEFAULT_ITERATIONS: u32 = 10_000;
+
+/// Наибольшее число итераций PBKDF2 при расшифровании по умолчанию / Default PBKDF2 iteration limit for decryption
+pub(crate) const MAX_ITERATIONS: u32 = 1_000_000;
+
+/// Длина соли PBKDF2 в байтах / PBKDF2 salt length in bytes
+pub(crate) const SALT_LEN: usize = 16;
+
+/// Источник ключа, затирается при удалении / Key source, wiped on drop
+pub(crate) enum KeySource {
+    /// Мастер-ключ / Master key
+    Raw(Block256),
+    /// Пароль; параметры PBKDF2 пишутся в начало шифртекста / Password; PBKDF2 params precede the ciphertext
+    Password(Vec<u8>),
+}
+
+impl Drop for KeySource {
+    fn drop(&mut self) {
+        match self {
+            KeySource::Raw(key) => key.zeroize(),
+            KeySource::Password(password) => password.zeroize(),
+        }
+    }
+}
+
+/// Разбор hex-строки, пробельные символы по краям отбрасываются / Parses a hex string, surrounding whitespace is ignored
+pub(crate) fn parse_hex(text: &[u8]) -> Result<Vec<u8>> {
+    let text = text.trim_ascii();
+    if !text.len().is_multiple_of(2) {
+        return Err("odd number of hex digits".into());
+    }
+
+    text.chunks_exact(2)
+        .map(|pair| Ok(hex_digit(pair[0])? << 4 | hex_digit(pair[1])?))
+        .collect()
+}
+
+fn hex_digit(c: u8) -> Result<u8> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(format!("invalid hex digit {:?}", c as char).into()),
+    }
+}
+
+pub(crate) fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 256-битный ключ из 64 hex-символов / 256-bit key from 64 hex digits
+pub(crate) fn parse_key(text: &[u8]) -> Result<Block256> {
+    let mut bytes = parse_hex(text)?;
+    let key = bytes.as_slice().try_into().map_err(|_| "the key must be 32 bytes (64 hex digits) long".into());
+    bytes.zeroize();
+    key
+}
+
+/// Файл ключа: ровно 32 байта или 64 hex-символа / Key file: exactly 32 raw bytes or 64 hex digits
+pub(crate) fn read_key_file(path: &Path) -> Result<Block256> {
+    let mut data = fs::read(path)?;
+    let key = match <Block256>::try_from(data.as_slice()) {
+        Ok(key) => Ok(key),
+        Err(_) => parse_key(&data),
+    };
+    data.zeroize();
+    key
+}
+
+/// Пароль с терминала; при `confirm` запрашивается повторно / Reads the password from the terminal, twice with `confirm`
+pub(crate) fn prompt_password(confirm: bool) -> Result<Vec<u8>> {
+    let password = rpassword::prompt_password("Password: ")?;
+    if confirm {
+        let mut repeated = rpassword::prompt_password("Repeat password: ")?;
+        let matches = repeated == password;
+        repeated.zeroize();
+        if !matches {
+            let mut password = password;
+            password.zeroize();
+            return Err("passwords do not match".into());
+        }
+    }
+    Ok(password.into_bytes())
+}
+
+/// Случайные байты ОС / Random bytes from the OS
+pub(crate) fn random_bytes(len: usize) -> Result<Vec<u8>> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("random generator failed: {}", e))?;
+    Ok(bytes)
+}
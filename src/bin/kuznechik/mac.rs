@@ -0,0 +1,61 @@
+use std::io::{self, Read, Write};
+
+use kuznechik::{AlgMac, KeyStore, Kuznechik};
+
+use super::key;
+use super::Result;
+
+/// Размер части файла, подаваемой в `AlgMac::update` / Chunk size fed to `AlgMac::update`
+const CHUNK_LEN: usize = 64 * 1024;
+
+/// Имитовставка длины `tag_len` потока `input` / MAC of `tag_len` bytes over the stream `input`
+pub(crate) fn compute(kuz: &KeyStore, tag_len: usize, input: &mut dyn Read) -> Result<Vec<u8>> {
+    let mut mac = AlgMac::new(kuz).tag_len(tag_len)?;
+    feed(&mut mac, input)?;
+    Ok(mac.finalize())
+}
+
+///
+/// Проверка списка `<hex>  <файл>` в формате вывода `kuznechik mac`; длина имитовставки берётся из записи.
+/// Verifies a `<hex>  <file>` list as printed by `kuznechik mac`; the tag length is taken from each line.
+///
+/// Имитовставки сравниваются за постоянное время. Для каждого файла печатается `OK` или `FAILED`;
+/// возвращает `true`, только если все имитовставки совпали.
+///
+pub(crate) fn verify(kuz: &KeyStore, tags: &str, open: impl Fn(&str) -> Result<Box<dyn Read>>,
+                     output: &mut dyn Write) -> Result<bool> {
+    let mut all_valid = true;
+
+    for (number, line) in tags.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let (tag, name) = line.trim().split_once(char::is_whitespace)
+            .ok_or_else(|| format!("line {}: expected `<tag>  <file>`", number + 1))?;
+        let tag = key::parse_hex(tag.as_bytes()).map_err(|e| format!("line {}: {}", number + 1, e))?;
+        let name = name.trim_start();
+
+        let mut mac = AlgMac::new(kuz).tag_len(tag.len()).map_err(|e| format!("line {}: {}", number + 1, e))?;
+        let is_valid = match open(name).and_then(|mut input| feed(&mut mac, &mut input)) {
+            Ok(()) => mac.verify(&tag).is_ok(),
+            Err(e) => {
+                writeln!(output, "{}: FAILED ({})", name, e)?;
+                all_valid = false;
+                continue;
+            }
+        };
+
+        writeln!(output, "{}: {}", name, if is_valid { "OK" } else { "FAILED" })?;
+        all_valid &= is_valid;
+    }
+    Ok(all_valid)
+}
+
+fn feed(mac: &mut AlgMac, input: &mut dyn Read) -> Result<()> {
+    let mut chunk = vec![0u8; CHUNK_LEN];
+    loop {
+        match input.read(&mut chunk) {
+            Ok(0) => return Ok(()),
+            Ok(n) => mac.update(&chunk[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
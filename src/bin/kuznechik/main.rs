@@ -0,0 +1,218 @@
+//! Утилита командной строки "Кузнечик" / "Kuznechik" command-line tool
+//!
+//! ```text
+//! kuznechik encrypt --mode cbc --password secret.txt -o secret.enc
+//! kuznechik decrypt --mode cbc --password secret.enc -o secret.txt
+//! kuznechik mac --key-file key.bin *.tar > tags.txt
+//! kuznechik mac --key-file key.bin --verify tags.txt
+//! ```
+
+mod key;
+mod crypt;
+mod mac;
+mod tests;
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use kuznechik::{Block256, KeyStore};
+use zeroize::Zeroize;
+
+use key::KeySource;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(name = "kuznechik", version, about = "GOST R 34.12-2015 / 34.13-2015 file encryption")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Зашифровать файл или stdin / Encrypt a file or stdin
+    Encrypt(CryptArgs),
+    /// Расшифровать файл или stdin / Decrypt a file or stdin
+    Decrypt(CryptArgs),
+    /// Выработать или проверить имитовставки файлов / Compute or verify file MACs (imitovstavka)
+    Mac(MacArgs),
+}
+
+/// Режим шифрования ГОСТ Р 34.13-2015 / GOST R 34.13-2015 mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Mode {
+    Ecb,
+    Cbc,
+    Ctr,
+    Ofb,
+    Cfb,
+}
+
+#[derive(Args)]
+struct CryptArgs {
+    /// Режим шифрования / Encryption mode
+    #[arg(short, long, value_enum, default_value_t = Mode::Cbc)]
+    mode: Mode,
+
+    #[command(flatten)]
+    key: KeyArgs,
+
+    /// Синхропосылка в hex; без неё при зашифровании создаётся случайная и пишется в начало вывода
+    /// IV in hex; without it encryption generates one and writes it in front of the ciphertext
+    #[arg(long, value_name = "HEX")]
+    iv: Option<String>,
+
+    /// Число итераций PBKDF2 для нового ключа из пароля / PBKDF2 iterations for a new password key
+    #[arg(long, default_value_t = key::DEFAULT_ITERATIONS)]
+    iterations: u32,
+
+    /// Наибольшее число итераций PBKDF2 из заголовка при расшифровании / Largest PBKDF2 iteration count accepted when decrypting
+    #[arg(long, default_value_t = key::MAX_ITERATIONS)]
+    max_iterations: u32,
+
+    /// Входной файл, `-` или без аргумента - stdin / Input file, `-` or none for stdin
+    input: Option<PathBuf>,
+
+    /// Выходной файл, `-` или без аргумента - stdout / Output file, `-` or none for stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct KeyArgs {
+    /// Запросить пароль (PBKDF2-HMAC-Стрибог-512) / Prompt for a password (PBKDF2-HMAC-Streebog-512)
+    #[arg(short, long)]
+    password: bool,
+
+    /// 256-битный ключ в hex / 256-bit key in hex
+    #[arg(short, long, value_name = "HEX")]
+    key: Option<String>,
+
+    /// Файл с ключом: 32 байта или 64 hex-символа / Key file: 32 raw bytes or 64 hex digits
+    #[arg(long, value_name = "FILE")]
+    key_file: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct MacArgs {
+    #[command(flatten)]
+    key: MacKeyArgs,
+
+    /// Длина имитовставки s в байтах / Tag length s in bytes
+    #[arg(short, long, default_value_t = 8, value_parser = clap::value_parser!(u8).range(1..=16))]
+    len: u8,
+
+    /// Проверить имитовставки из файла в формате вывода `mac` / Verify the tags listed in a file in `mac` output format
+    #[arg(long, value_name = "TAGFILE", conflicts_with = "files")]
+    verify: Option<PathBuf>,
+
+    /// Файлы, `-` или без аргументов - stdin / Files, `-` or none for stdin
+    files: Vec<PathBuf>,
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct MacKeyArgs {
+    /// 256-битный ключ в hex / 256-bit key in hex
+    #[arg(short, long, value_name = "HEX")]
+    key: Option<String>,
+
+    /// Файл с ключом: 32 байта или 64 hex-символа / Key file: 32 raw bytes or 64 hex digits
+    #[arg(long, value_name = "FILE")]
+    key_file: Option<PathBuf>,
+}
+
+impl MacKeyArgs {
+    fn load(&self) -> Result<Block256> {
+        match (&self.key, &self.key_file) {
+            (Some(hex), _) => key::parse_key(hex.as_bytes()),
+            (_, Some(path)) => key::read_key_file(path),
+            _ => Err("no key given".into()),
+        }
+    }
+}
+
+impl KeyArgs {
+    fn source(&self, confirm: bool) -> Result<KeySource> {
+        if let Some(hex) = &self.key {
+            return Ok(KeySource::Raw(key::parse_key(hex.as_bytes())?));
+        }
+        if let Some(path) = &self.key_file {
+            return Ok(KeySource::Raw(key::read_key_file(path)?));
+        }
+        Ok(KeySource::Password(key::prompt_password(confirm)?))
+    }
+}
+
+fn open_input(path: &Option<PathBuf>) -> Result<Box<dyn Read>> {
+    Ok(match path {
+        Some(path) if path.as_os_str() != "-" => Box::new(BufReader::new(File::open(path)?)),
+        _ => Box::new(io::stdin().lock()),
+    })
+}
+
+fn open_output(path: &Option<PathBuf>) -> Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) if path.as_os_str() != "-" => Box::new(BufWriter::new(File::create(path)?)),
+        _ => Box::new(io::stdout().lock()),
+    })
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    match cli.command {
+        Command::Encrypt(args) => {
+            let key = args.key.source(true)?;
+            let iv = args.iv.as_deref().map(|hex| key::parse_hex(hex.as_bytes())).transpose()?;
+            let mut input = open_input(&args.input)?;
+            let mut output = open_output(&args.output)?;
+            crypt::encrypt(args.mode, &key, iv.as_deref(), args.iterations, &mut input, &mut output)?;
+        }
+        Command::Decrypt(args) => {
+            let key = args.key.source(false)?;
+            let iv = args.iv.as_deref().map(|hex| key::parse_hex(hex.as_bytes())).transpose()?;
+            let mut input = open_input(&args.input)?;
+            let mut output = open_output(&args.output)?;
+            crypt::decrypt(args.mode, &key, iv.as_deref(), args.max_iterations, &mut input, &mut output)?;
+        }
+        Command::Mac(args) => return run_mac(args),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Печатает имитовставки файлов или проверяет их; при несовпадении код возврата 1
+/// Prints the tags of the files or verifies them; exits with 1 on a mismatch
+fn run_mac(args: MacArgs) -> Result<ExitCode> {
+    let mut master_key = args.key.load()?;
+    let kuz = KeyStore::with_master_key(master_key);
+    master_key.zeroize();
+
+    let mut output = io::stdout().lock();
+    if let Some(tag_file) = &args.verify {
+        let tags = std::fs::read_to_string(tag_file)?;
+        let all_valid = mac::verify(&kuz, &tags, |name| open_input(&Some(name.into())), &mut output)?;
+        return Ok(if all_valid { ExitCode::SUCCESS } else { ExitCode::FAILURE });
+    }
+
+    let files = if args.files.is_empty() { vec![PathBuf::from("-")] } else { args.files };
+    for path in &files {
+        let tag = mac::compute(&kuz, args.len as usize, &mut open_input(&Some(path.clone()))?)?;
+        writeln!(output, "{}  {}", key::to_hex(&tag), path.display())?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("kuznechik: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
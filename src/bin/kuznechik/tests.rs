@@ -0,0 +1,173 @@
+#![cfg(test)]
+
+use std::io::Read;
+
+use kuznechik::{AlgCbc, AlgCtr, AlgMac, KeyStore, Kuznechik, PasswordParams};
+
+use super::{mac, Mode};
+use super::crypt::{decrypt, encrypt};
+use super::key::{parse_hex, parse_key, to_hex, KeySource, MAX_ITERATIONS};
+
+const KEY: [u8; 32] = [0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+    0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+
+const MODES: [Mode; 5] = [Mode::Ecb, Mode::Cbc, Mode::Ctr, Mode::Ofb, Mode::Cfb];
+
+fn encrypt_to_vec(mode: Mode, key: &KeySource, iv: Option<&[u8]>, data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    encrypt(mode, key, iv, 1, &mut &data[..], &mut output).unwrap();
+    output
+}
+
+fn decrypt_to_vec(mode: Mode, key: &KeySource, iv: Option<&[u8]>, data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut output = Vec::new();
+    decrypt(mode, key, iv, MAX_ITERATIONS, &mut &data[..], &mut output).map_err(|e| e.to_string())?;
+    Ok(output)
+}
+
+#[test]
+fn encrypt_decrypt_generated_iv() {
+    // Assign
+    let key = KeySource::Raw(KEY);
+    let data: Vec<u8> = (0..20000u32).map(|i| (i * 7) as u8).collect();
+
+    for mode in MODES {
+        // Act
+        let enc_data = encrypt_to_vec(mode, &key, None, &data);
+        let dec_data = decrypt_to_vec(mode, &key, None, &enc_data);
+
+        // Assert
+        assert!(enc_data.len() >= data.len() + mode.iv_len(), "{:?}", mode);
+        assert_eq!(dec_data.unwrap(), data, "{:?}", mode);
+    }
+}
+
+#[test]
+fn encrypt_matches_library() {
+    // Assign
+    let key = KeySource::Raw(KEY);
+    let kuz = KeyStore::with_master_key(KEY);
+    let data = b"Hello, World! Hello, World! Hello, World!".to_vec();
+    let iv: Vec<u8> = (0..16).collect();
+
+    // Act
+    let cbc = encrypt_to_vec(Mode::Cbc, &key, Some(&iv), &data);
+    let ctr = encrypt_to_vec(Mode::Ctr, &key, Some(&iv[..8]), &data);
+
+    // Assert
+    assert_eq!(cbc, AlgCbc::new(&kuz).gamma(iv.clone()).encrypt(data.clone()));
+    assert_eq!(ctr, AlgCtr::new(&kuz).gamma([&iv[..8], &[0u8; 8]].concat()).encrypt(data));
+}
+
+#[test]
+fn encrypt_decrypt_password() {
+    // Assign
+    let key = KeySource::Password(b"Kuznechik".to_vec());
+    let data = b"Hello, World!".to_vec();
+
+    // Act
+    let enc_data = encrypt_to_vec(Mode::Ofb, &key, None, &data);
+    let (params, rest) = PasswordParams::from_bytes(&enc_data).unwrap();
+    let dec_data = decrypt_to_vec(Mode::Ofb, &key, None, &enc_data);
+    let wrong = decrypt_to_vec(Mode::Ofb, &KeySource::Password(b"Kuznechik!".to_vec()), None, &enc_data);
+
+    // Assert
+    assert_eq!(params.iterations(), 1);
+    assert_eq!(rest.len(), 16 + data.len());
+    assert_eq!(dec_data.unwrap(), data);
+    assert_ne!(wrong.unwrap(), data);
+}
+
+#[test]
+fn decrypt_rejects_too_many_iterations() {
+    // Assign
+    let key = KeySource::Password(b"Kuznechik".to_vec());
+    let mut enc_data = encrypt_to_vec(Mode::Ctr, &key, None, b"Hello, World!");
+    enc_data[1..5].copy_from_slice(&u32::MAX.to_be_bytes());
+
+    // Act
+    let mut output = Vec::new();
+    let forged = decrypt(Mode::Ctr, &key, None, MAX_ITERATIONS, &mut &enc_data[..], &mut output).map_err(|e| e.to_string());
+
+    // Assert
+    assert_eq!(forged.unwrap_err(), format!("the header asks for {} PBKDF2 iterations, more than --max-iterations {}",
+                                            u32::MAX, MAX_ITERATIONS));
+    assert!(output.is_empty());
+}
+
+#[test]
+fn decrypt_errors() {
+    // Assign
+    let key = KeySource::Raw(KEY);
+    let enc_data = encrypt_to_vec(Mode::Cbc, &key, None, b"Hello, World!");
+
+    // Act
+    let truncated = decrypt_to_vec(Mode::Cbc, &key, None, &enc_data[..enc_data.len() - 1]);
+    let short_header = decrypt_to_vec(Mode::Cbc, &key, None, &enc_data[..10]);
+    let ecb_iv = decrypt_to_vec(Mode::Ecb, &key, Some(&[0u8; 16]), &enc_data);
+    let ctr_iv = decrypt_to_vec(Mode::Ctr, &key, Some(&[0u8; 16]), &enc_data);
+
+    // Assert
+    assert!(truncated.is_err());
+    assert_eq!(short_header.unwrap_err(), "the input is too short for its header");
+    assert_eq!(ecb_iv.unwrap_err(), "ECB does not use an IV");
+    assert_eq!(ctr_iv.unwrap_err(), "CTR needs an IV of 8 bytes (n/2)");
+}
+
+#[test]
+fn parse_hex_key() {
+    // Assign
+    let text = b"8899aabbccddeeff0011223344556677FEDCBA98765432100123456789ABCDEF\n";
+
+    // Act
+    let key = parse_key(text).unwrap();
+
+    // Assert
+    assert_eq!(key, KEY);
+    assert_eq!(parse_hex(b" 0a1B ").unwrap(), vec![0x0a, 0x1b]);
+    assert!(parse_hex(b"abc").is_err());
+    assert!(parse_hex(b"zz").is_err());
+    assert!(parse_key(b"00").is_err());
+}
+
+#[test]
+fn mac_compute_verify() {
+    // Assign
+    let kuz = KeyStore::with_master_key(KEY);
+    let files: [(&str, Vec<u8>); 3] = [
+        ("empty.bin", vec![]),
+        ("large.bin", (0..70_000u32).map(|i| (i % 251) as u8).collect()),
+        ("name with spaces.txt", b"Hello, World!".to_vec()),
+    ];
+    let open = |name: &str| -> super::Result<Box<dyn Read>> {
+        let (_, data) = files.iter().find(|(file, _)| *file == name).ok_or("no such file")?;
+        Ok(Box::new(std::io::Cursor::new(data.clone())))
+    };
+
+    // Act
+    let mut tags = String::new();
+    for (name, data) in &files {
+        let tag = mac::compute(&kuz, 16, &mut &data[..]).unwrap();
+        assert_eq!(tag, AlgMac::new(&kuz).tag_len(16).unwrap().encrypt(data.clone()));
+        tags += &format!("{}  {}\n", to_hex(&tag), name);
+    }
+
+    let mut report = Vec::new();
+    let valid = mac::verify(&kuz, &tags, open, &mut report).unwrap();
+
+    let forged = tags.replacen(&tags[..2], if &tags[..2] == "00" { "01" } else { "00" }, 1);
+    let mut forged_report = Vec::new();
+    let forged_valid = mac::verify(&kuz, &forged, open, &mut forged_report).unwrap();
+
+    let mut missing_report = Vec::new();
+    let missing_valid = mac::verify(&kuz, &format!("{}  missing.bin\n", &tags[..32]), open, &mut missing_report).unwrap();
+
+    // Assert
+    assert!(valid);
+    assert_eq!(String::from_utf8(report).unwrap(), "empty.bin: OK\nlarge.bin: OK\nname with spaces.txt: OK\n");
+    assert!(!forged_valid);
+    assert!(String::from_utf8(forged_report).unwrap().starts_with("empty.bin: FAILED\nlarge.bin: OK"));
+    assert!(!missing_valid);
+    assert_eq!(String::from_utf8(missing_report).unwrap(), "missing.bin: FAILED (no such file)\n");
+    assert!(mac::verify(&kuz, "zz  empty.bin", open, &mut Vec::new()).is_err());
+}
@@ -0,0 +1,238 @@
+mod tests;
+
+use alloc::{boxed::Box, vec::Vec};
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+use crate::{CtrOmac, KeyStore};
+use crate::kdf::PasswordParams;
+use crate::error::KuznechikError;
+
+/**
+* # Зашифрованный контейнер<br>Self-describing encrypted container
+*
+* Контейнер хранит всё, что нужно для расшифрования, кроме ключа: алгоритм, параметры PBKDF2, синхропосылку
+* и имитовставку. Данные зашифровываются в режиме CTR, затем на отдельном ключе вырабатывается имитовставка OMAC
+* (`AlgMac`, n бит) над синхропосылкой, заголовком и шифртекстом - это [`CtrOmac`](crate::CtrOmac) с заголовком
+* в роли ассоциированных данных.
+* The tag is checked in constant time before anything is decrypted, so a modified container never yields plaintext.
+*
+* Формат / Layout (числа - big-endian):
+*
+* | Поле / Field | Байт / Bytes | |
+* |:--|:--|:--|
+* | magic | 4 | `KUZN` |
+* | version | 1 | 1 |
+* | algorithm | 1 | 1 = Кузнечик CTR + OMAC / Kuznechik CTR + OMAC |
+* | kdf | 1 | 0 = ключ / raw key, 1 = PBKDF2 |
+* | kdf params | 7 + соль / salt | `PasswordParams::to_bytes`, только для / only for kdf = 1 |
+* | iv length | 1 | 8 (n/2) |
+* | iv | 8 | |
+* | ciphertext | * | |
+* | tag | 16 | OMAC(iv \|\| header \|\| ciphertext \|\| len(header) \|\| len(ciphertext)) |
+*
+* Заголовок - все поля от magic до длины синхропосылки, длины - 64-битные числа байт.
+*
+* # Использование / Usage:
+* ```
+* use kuznechik::{KeyStore, ContainerReader, ContainerWriter, KuznechikError};
+*
+* let kuz = KeyStore::with_password("Kuznechik");
+* let iv = [0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xce, 0xf0];
+*
+* let mut container = ContainerWriter::new(&kuz, &iv).unwrap().seal(b"Hello, World!").unwrap();
+* assert_eq!(ContainerReader::new(&kuz).open(&container).unwrap(), b"Hello, World!");
+*
+* let last = container.len() - 1;
+* container[last] ^= 1;
+* assert_eq!(ContainerReader::new(&kuz).open(&container), Err(KuznechikError::AuthenticationFailed));
+* ```
+**/
+pub struct ContainerWriter<'a> {
+    key: KeyRef<'a>,
+    params: Option<&'a PasswordParams>,
+    iv: [u8; IV_LEN],
+}
+
+/// Чтение контейнера [`ContainerWriter`] / Reads containers made by [`ContainerWriter`]
+pub struct ContainerReader<'a> {
+    key: ReaderKey<'a>,
+    max_iterations: u32,
+}
+
+const MAGIC: [u8; 4] = *b"KUZN";
+const VERSION: u8 = 1;
+
+/// Кузнечик, CTR + OMAC / Kuznechik, CTR then OMAC
+const ALG_KUZNECHIK_CTR_OMAC: u8 = 1;
+
+const KDF_NONE: u8 = 0;
+const KDF_PBKDF2: u8 = 1;
+
+/// Длина синхропосылки CTR, n/2 / CTR IV length, n/2
+const IV_LEN: usize = 8;
+const TAG_LEN: usize = 16;
+
+enum KeyRef<'a> {
+    Borrowed(&'a KeyStore),
+    Owned(Box<KeyStore>),
+}
+
+impl KeyRef<'_> {
+    fn get(&self) -> &KeyStore {
+        match self {
+            KeyRef::Borrowed(key) => key,
+            KeyRef::Owned(key) => key,
+        }
+    }
+}
+
+enum ReaderKey<'a> {
+    KeyStore(&'a KeyStore),
+    Password(&'a [u8]),
+}
+
+impl<'a> ContainerWriter<'a> {
+    /// Синхропосылка длины n/2 не должна повторяться на одном ключе / The n/2 IV must never repeat under one key
+    pub fn new(key: &'a KeyStore, iv: &[u8]) -> Result<Self, KuznechikError> {
+        Ok(ContainerWriter { key: KeyRef::Borrowed(key), params: None, iv: check_iv(iv)? })
+    }
+
+    /// Ключ вырабатывается из пароля, параметры PBKDF2 записываются в заголовок
+    /// The key is derived from the password; the PBKDF2 params are stored in the header
+    pub fn with_password(password: &[u8], params: &'a PasswordParams, iv: &[u8]) -> Result<Self, KuznechikError> {
+        Ok(ContainerWriter {
+            key: KeyRef::Owned(Box::new(KeyStore::with_password_and_params(password, params))),
+            params: Some(params),
+            iv: check_iv(iv)?,
+        })
+    }
+
+    /// Контейнер с данными `data` / Container holding `data`
+    pub fn seal(&self, data: &[u8]) -> Result<Vec<u8>, KuznechikError> {
+        let mut container = Vec::with_capacity(32 + data.len() + TAG_LEN);
+        container.extend_from_slice(&MAGIC);
+        container.push(VERSION);
+        container.push(ALG_KUZNECHIK_CTR_OMAC);
+        match self.params {
+            None => container.push(KDF_NONE),
+            Some(params) => {
+                container.push(KDF_PBKDF2);
+                container.extend_from_slice(&params.to_bytes());
+            }
+        }
+        container.push(IV_LEN as u8);
+
+        let header_len = container.len();
+        container.extend_from_slice(&self.iv);
+        let body_start = container.len();
+        container.extend_from_slice(data);
+
+        let (head, body) = container.split_at_mut(body_start);
+        let tag = CtrOmac::new(self.key.get()).encrypt_in_place_detached(&self.iv, &head[..header_len], body)?;
+        container.extend_from_slice(&tag);
+        Ok(container)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write<W: Write>(&self, mut out: W, data: &[u8]) -> io::Result<()> {
+        out.write_all(&self.seal(data)?)
+    }
+}
+
+impl<'a> ContainerReader<'a> {
+    /// Наибольшее число итераций PBKDF2 по умолчанию / Default limit on the PBKDF2 iteration count
+    pub const DEFAULT_MAX_ITERATIONS: u32 = 1_000_000;
+
+    pub fn new(key: &'a KeyStore) -> Self {
+        ContainerReader { key: ReaderKey::KeyStore(key), max_iterations: Self::DEFAULT_MAX_ITERATIONS }
+    }
+
+    /// Ключ вырабатывается из пароля по параметрам PBKDF2 из заголовка
+    /// The key is derived from the password with the PBKDF2 params from the header
+    pub fn with_password(password: &'a [u8]) -> Self {
+        ContainerReader { key: ReaderKey::Password(password), max_iterations: Self::DEFAULT_MAX_ITERATIONS }
+    }
+
+    ///
+    /// Наибольшее допустимое число итераций PBKDF2 / Largest accepted PBKDF2 iteration count
+    ///
+    /// Заголовок ещё не проверен при выработке ключа, поэтому без предела поддельный контейнер с `u32::MAX`
+    /// итераций занял бы процессор надолго. A container above the limit is rejected with `InvalidContainer`
+    /// before the key is derived.
+    ///
+    pub fn max_iterations(mut self, max_iterations: u32) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    ///
+    /// Данные контейнера; имитовставка проверяется до расшифрования
+    /// The container data; the tag is verified before decryption
+    ///
+    /// `InvalidContainer` - повреждённый заголовок или число итераций PBKDF2 выше [`max_iterations`](Self::max_iterations),
+    /// `AuthenticationFailed` - неверный ключ или изменённые данные.
+    ///
+    pub fn open(&self, container: &[u8]) -> Result<Vec<u8>, KuznechikError> {
+        let header = Header::parse(container)?;
+
+        let key = match (&self.key, header.params) {
+            (ReaderKey::KeyStore(key), _) => KeyRef::Borrowed(key),
+            (ReaderKey::Password(_), Some(params)) if params.iterations() > self.max_iterations => {
+                return Err(KuznechikError::InvalidContainer)
+            }
+            (ReaderKey::Password(password), Some(params)) => KeyRef::Owned(Box::new(KeyStore::with_password_and_params(password, &params))),
+            (ReaderKey::Password(_), None) => return Err(KuznechikError::InvalidPasswordParams),
+        };
+
+        let (header_bytes, rest) = container.split_at(header.len);
+        let (iv, rest) = rest.split_at(IV_LEN);
+        let (cipher_text, tag) = rest.split_at(rest.len() - TAG_LEN);
+
+        let mut data = cipher_text.to_vec();
+        CtrOmac::new(key.get()).decrypt_in_place_detached(iv, header_bytes, &mut data, tag)?;
+        Ok(data)
+    }
+
+    /// Читает контейнер до конца потока / Reads the container up to the end of the stream
+    #[cfg(feature = "std")]
+    pub fn read<R: Read>(&self, mut input: R) -> io::Result<Vec<u8>> {
+        let mut container = Vec::new();
+        input.read_to_end(&mut container)?;
+        Ok(self.open(&container)?)
+    }
+}
+
+/// Разобранный заголовок / Parsed header
+struct Header {
+    params: Option<PasswordParams>,
+    /// Длина заголовка до синхропосылки / Header length up to the IV
+    len: usize,
+}
+
+impl Header {
+    fn parse(container: &[u8]) -> Result<Self, KuznechikError> {
+        if container.len() < 7 || container[..4] != MAGIC || container[4] != VERSION || container[5] != ALG_KUZNECHIK_CTR_OMAC {
+            return Err(KuznechikError::InvalidContainer);
+        }
+
+        let (params, rest) = match container[6] {
+            KDF_NONE => (None, &container[7..]),
+            KDF_PBKDF2 => {
+                let (params, rest) = PasswordParams::from_bytes(&container[7..])
+                    .map_err(|_| KuznechikError::InvalidContainer)?;
+                (Some(params), rest)
+            }
+            _ => return Err(KuznechikError::InvalidContainer),
+        };
+
+        if rest.first() != Some(&(IV_LEN as u8)) || rest.len() < 1 + IV_LEN + TAG_LEN {
+            return Err(KuznechikError::InvalidContainer);
+        }
+        Ok(Header { params, len: container.len() - rest.len() + 1 })
+    }
+}
+
+fn check_iv(iv: &[u8]) -> Result<[u8; IV_LEN], KuznechikError> {
+    iv.try_into().map_err(|_| KuznechikError::InvalidGammaLength)
+}
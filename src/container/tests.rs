@@ -0,0 +1,134 @@
+#![cfg(test)]
+
+use super::{ContainerReader, ContainerWriter};
+use crate::{KeyStore, KuznechikError, PasswordParams, Prf};
+
+const IV: [u8; 8] = [0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xce, 0xf0];
+
+#[test]
+fn seal_open() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let writer = ContainerWriter::new(&kuz, &IV).unwrap();
+
+    for len in [0, 1, 15, 16, 17, 100] {
+        let data: Vec<u8> = (0..len as u8).collect();
+
+        // Act
+        let container = writer.seal(&data).unwrap();
+        let opened = ContainerReader::new(&kuz).open(&container);
+
+        // Assert
+        assert_eq!(&container[..8], b"KUZN\x01\x01\x00\x08");
+        assert_eq!(&container[8..16], &IV);
+        assert_eq!(container.len(), 16 + len + 16);
+        assert!(len == 0 || container[16..16 + len] != data[..]);
+        assert_eq!(opened.unwrap(), data);
+    }
+}
+
+#[test]
+fn seal_open_password() {
+    // Assign
+    let params = PasswordParams::new(b"random salt 1234".to_vec(), 2, Prf::HmacStreebog512).unwrap();
+    let writer = ContainerWriter::with_password(b"password", &params, &IV).unwrap();
+
+    // Act
+    let container = writer.seal(b"Hello, World!").unwrap();
+    let opened = ContainerReader::with_password(b"password").open(&container);
+    let wrong_password = ContainerReader::with_password(b"passw0rd").open(&container);
+    let key_store = ContainerReader::new(&KeyStore::with_password_and_params(b"password", &params)).open(&container);
+
+    let raw = ContainerWriter::new(&KeyStore::with_password("Kuznechik"), &IV).unwrap().seal(b"Hello").unwrap();
+    let password_for_raw = ContainerReader::with_password(b"password").open(&raw);
+
+    // Assert
+    assert_eq!(&container[7..30], &params.to_bytes()[..]);
+    assert_eq!(opened.unwrap(), b"Hello, World!");
+    assert_eq!(wrong_password, Err(KuznechikError::AuthenticationFailed));
+    assert_eq!(key_store.unwrap(), b"Hello, World!");
+    assert_eq!(password_for_raw, Err(KuznechikError::InvalidPasswordParams));
+}
+
+#[test]
+fn rejects_too_many_iterations() {
+    // Assign
+    let params = PasswordParams::new(b"random salt 1234".to_vec(), 3, Prf::HmacStreebog512).unwrap();
+    let container = ContainerWriter::with_password(b"password", &params, &IV).unwrap().seal(b"Hello").unwrap();
+
+    // Поддельный заголовок с u32::MAX итераций: без предела PBKDF2 выполнялся бы практически бесконечно
+    let mut forged = container.clone();
+    forged[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+
+    // Act
+    let forged_default = ContainerReader::with_password(b"password").open(&forged);
+    let below_limit = ContainerReader::with_password(b"password").max_iterations(2).open(&container);
+    let at_limit = ContainerReader::with_password(b"password").max_iterations(3).open(&container);
+
+    // Assert
+    assert_eq!(forged_default, Err(KuznechikError::InvalidContainer));
+    assert_eq!(below_limit, Err(KuznechikError::InvalidContainer));
+    assert_eq!(at_limit.unwrap(), b"Hello");
+}
+
+#[test]
+fn detects_tampering() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let container = ContainerWriter::new(&kuz, &IV).unwrap().seal(b"Hello, World! Hello, World!").unwrap();
+
+    for i in 0..container.len() {
+        let mut modified = container.clone();
+        modified[i] ^= 0x01;
+
+        // Act
+        let opened = ContainerReader::new(&kuz).open(&modified);
+
+        // Assert
+        let expected = match i {
+            0..=7 => KuznechikError::InvalidContainer,
+            _ => KuznechikError::AuthenticationFailed,
+        };
+        assert_eq!(opened, Err(expected), "byte {}", i);
+    }
+
+    let other_key = KeyStore::with_password("Kuznechik!");
+    assert_eq!(ContainerReader::new(&other_key).open(&container), Err(KuznechikError::AuthenticationFailed));
+}
+
+#[test]
+fn invalid_containers() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let container = ContainerWriter::new(&kuz, &IV).unwrap().seal(b"").unwrap();
+    let reader = ContainerReader::new(&kuz);
+
+    // Act
+    let truncated: Vec<_> = (0..container.len()).map(|len| reader.open(&container[..len])).collect();
+    let bad_iv = ContainerWriter::new(&kuz, &[0u8; 16]).err();
+
+    // Assert
+    assert!(truncated.iter().all(|result| *result == Err(KuznechikError::InvalidContainer)));
+    assert_eq!(reader.open(&container), Ok(vec![]));
+    assert_eq!(bad_iv, Some(KuznechikError::InvalidGammaLength));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn write_read() {
+    // Assign
+    let kuz = KeyStore::with_password("Kuznechik");
+    let data: Vec<u8> = (0..10000u32).map(|i| (i * 13) as u8).collect();
+
+    // Act
+    let mut file = Vec::new();
+    ContainerWriter::new(&kuz, &IV).unwrap().write(&mut file, &data).unwrap();
+    let read = ContainerReader::new(&kuz).read(&file[..]);
+
+    file[100] ^= 0x80;
+    let tampered = ContainerReader::new(&kuz).read(&file[..]);
+
+    // Assert
+    assert_eq!(read.unwrap(), data);
+    assert_eq!(tampered.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,61 @@
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+/// Ошибки режимов шифрования / Errors of the encryption modes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KuznechikError {
+    /// Гамма (синхропосылка) недопустимой длины / Gamma (IV) has an invalid length
+    InvalidGammaLength,
+    /// Длина шифртекста не кратна длине блока / Ciphertext length is not a multiple of the block length
+    InvalidDataLength,
+    /// Некорректное дополнение / Padding of the decrypted data is malformed
+    InvalidPadding,
+    /// Операция не поддерживается режимом / Operation is not supported by the mode
+    UnsupportedOperation,
+    /// Недопустимый nonce / Nonce has an invalid length or value
+    InvalidNonce,
+    /// Недопустимая длина имитовставки / Authentication tag length is out of range
+    InvalidTagLength,
+    /// Имитовставка не совпала / Authentication tag mismatch
+    AuthenticationFailed,
+    /// Недопустимая длина секции ACPKM / ACPKM section size is not a positive multiple of the block length
+    InvalidSectionSize,
+    /// Недопустимые параметры выработки ключа из пароля / Password-based key derivation params are malformed
+    InvalidPasswordParams,
+    /// В буфере нет места для дополнения / Buffer has no room for the padded data
+    BufferTooSmall,
+    /// Повреждённый или неизвестный заголовок контейнера / Container header is malformed or unsupported
+    InvalidContainer,
+    /// Генератор случайных чисел ОС недоступен / The OS random number generator failed
+    RandomFailed,
+}
+
+impl fmt::Display for KuznechikError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            KuznechikError::InvalidGammaLength => "invalid gamma length",
+            KuznechikError::InvalidDataLength => "data length is not a multiple of the block length",
+            KuznechikError::InvalidPadding => "invalid padding",
+            KuznechikError::UnsupportedOperation => "operation is not supported by the mode",
+            KuznechikError::InvalidNonce => "invalid nonce",
+            KuznechikError::InvalidTagLength => "invalid tag length",
+            KuznechikError::AuthenticationFailed => "authentication failed",
+            KuznechikError::InvalidSectionSize => "invalid section size",
+            KuznechikError::InvalidPasswordParams => "invalid password params",
+            KuznechikError::BufferTooSmall => "buffer is too small",
+            KuznechikError::InvalidContainer => "invalid container",
+            KuznechikError::RandomFailed => "random number generator failed",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl core::error::Error for KuznechikError {}
+
+#[cfg(feature = "std")]
+impl From<KuznechikError> for io::Error {
+    fn from(err: KuznechikError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
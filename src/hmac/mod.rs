@@ -0,0 +1,106 @@
+mod tests;
+
+use crate::streebog::{Digest, Streebog256, Streebog512};
+use crate::transforms::ct_eq;
+use crate::error::KuznechikError;
+use zeroize::Zeroize;
+
+/**
+* # HMAC на основе функции хэширования "Стрибог" (Р 50.1.113-2016, RFC 7836)<br>HMAC based on the Streebog hash function (R 50.1.113-2016, RFC 7836)
+*
+* HMAC(K, T) = H((K ^ opad) || H((K ^ ipad) || T)). Ключ длиннее блока хэш-функции предварительно хэшируется.
+* A key longer than the hash block is hashed first.
+*
+* # Использование / Usage:
+* ```
+* use crate::kuznechik::HmacStreebog256;
+*
+* let key = b"secret key";
+*
+* let mut hmac = HmacStreebog256::new(key);
+* hmac.update(b"Hello, ");
+* hmac.update(b"World!");
+* let tag = hmac.finalize();
+*
+* let mut hmac = HmacStreebog256::new(key);
+* hmac.update(b"Hello, World!");
+* assert!(hmac.verify(&tag).is_ok());
+* ```
+**/
+#[derive(Clone)]
+pub struct Hmac<H: Digest> {
+    inner: H,
+    outer: H,
+}
+
+/// HMAC_GOSTR3411_2012_256
+pub type HmacStreebog256 = Hmac<Streebog256>;
+/// HMAC_GOSTR3411_2012_512
+pub type HmacStreebog512 = Hmac<Streebog512>;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+/// Наибольшая длина блока хэш-функции, ключ дополняется на стеке
+const MAX_BLOCK_SIZE: usize = 144;
+
+impl<H: Digest> Hmac<H> {
+    pub fn new(key: &[u8]) -> Self {
+        assert!(H::BLOCK_SIZE <= MAX_BLOCK_SIZE, "Hmac::new(): hash block is too long");
+        let mut buf = [0u8; MAX_BLOCK_SIZE];
+        let key_block = &mut buf[..H::BLOCK_SIZE];
+        if key.len() > H::BLOCK_SIZE {
+            let mut hasher = H::default();
+            hasher.update(key);
+            let mut digest = hasher.finalize();
+            key_block[..digest.as_ref().len()].copy_from_slice(digest.as_ref());
+            digest.as_mut().zeroize();
+        } else {
+            key_block[..key.len()].copy_from_slice(key);
+        }
+
+        let mut inner = H::default();
+        let mut outer = H::default();
+        let mut pad = [0u8; MAX_BLOCK_SIZE];
+        for (byte, hasher) in [(IPAD, &mut inner), (OPAD, &mut outer)] {
+            for (p, k) in pad.iter_mut().zip(key_block.iter()) {
+                *p = k ^ byte;
+            }
+            hasher.update(&pad[..H::BLOCK_SIZE]);
+        }
+        pad.zeroize();
+        buf.zeroize();
+
+        Hmac { inner, outer }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    pub fn finalize(self) -> H::Output {
+        let mut outer = self.outer;
+        let mut inner = self.inner.finalize();
+        outer.update(inner.as_ref());
+        inner.as_mut().zeroize();
+        outer.finalize()
+    }
+
+    /// Проверка имитовставки за постоянное время / Constant-time tag check
+    pub fn verify(self, tag: &[u8]) -> Result<(), KuznechikError> {
+        let mut expected = self.finalize();
+        let is_equal = ct_eq(expected.as_ref(), tag);
+        expected.as_mut().zeroize();
+
+        match is_equal {
+            true => Ok(()),
+            false => Err(KuznechikError::AuthenticationFailed),
+        }
+    }
+
+    pub fn mac(key: &[u8], data: &[u8]) -> H::Output {
+        let mut hmac = Self::new(key);
+        hmac.update(data);
+        hmac.finalize()
+    }
+}
@@ -0,0 +1,159 @@
+mod tests;
+
+use crate::streebog::Digest;
+use crate::hmac::Hmac;
+use zeroize::Zeroize;
+#[cfg(feature = "alloc")]
+use crate::{streebog::{Streebog256, Streebog512}, types::Block256, error::KuznechikError};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/**
+* # Выработка ключа из пароля PBKDF2 (Р 50.1.111-2016)<br>Password-based key derivation PBKDF2 (R 50.1.111-2016)
+*
+* [`PasswordParams`] хранит соль, число итераций и псевдослучайную функцию; его двоичное представление
+* сохраняется рядом с шифртекстом, чтобы при расшифровании получить тот же ключ.
+* [`PasswordParams`] holds the salt, the iteration count and the PRF; store its encoding with the ciphertext.
+*
+* # Использование / Usage:
+* ```
+* use crate::kuznechik::{KeyStore, PasswordParams, Prf, Kuznechik, AlgEcb};
+*
+* let params = PasswordParams::new(b"random salt 1234".to_vec(), 1000, Prf::HmacStreebog512).unwrap();
+* let key_store = KeyStore::with_password_and_params(b"password", &params);
+* let same = KeyStore::with_password_params(b"password", b"random salt 1234", 1000, Prf::HmacStreebog512).unwrap();
+*
+* // Параметры сохраняются вместе с шифртекстом / The params are stored with the ciphertext
+* let header = params.to_bytes();
+* let (stored, rest) = PasswordParams::from_bytes(&header).unwrap();
+*
+* assert!(rest.is_empty());
+* assert_eq!(stored, params);
+* assert_eq!(AlgEcb::new(&key_store).encrypt(vec![0; 16]), AlgEcb::new(&same).encrypt(vec![0; 16]));
+* ```
+*
+* `PasswordParams` требует функции `alloc`, `pbkdf2` работает и без неё / `PasswordParams` needs `alloc`, `pbkdf2` does not.
+**/
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordParams {
+    prf: Prf,
+    salt: Vec<u8>,
+    iterations: u32,
+}
+
+/// Псевдослучайная функция PBKDF2 / PBKDF2 pseudorandom function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prf {
+    /// HMAC_GOSTR3411_2012_512 (рекомендуется Р 50.1.111-2016 / recommended by R 50.1.111-2016)
+    HmacStreebog512,
+    /// HMAC_GOSTR3411_2012_256
+    HmacStreebog256,
+}
+
+#[cfg(feature = "alloc")]
+impl Prf {
+    fn id(self) -> u8 {
+        match self {
+            Prf::HmacStreebog512 => 1,
+            Prf::HmacStreebog256 => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, KuznechikError> {
+        match id {
+            1 => Ok(Prf::HmacStreebog512),
+            2 => Ok(Prf::HmacStreebog256),
+            _ => Err(KuznechikError::InvalidPasswordParams),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PasswordParams {
+    /// Соль не пустая и не длиннее 65535 байт, число итераций больше нуля
+    /// The salt must be non-empty and at most 65535 bytes, the iteration count must be positive
+    pub fn new(salt: Vec<u8>, iterations: u32, prf: Prf) -> Result<Self, KuznechikError> {
+        if salt.is_empty() || salt.len() > u16::MAX as usize || iterations == 0 {
+            return Err(KuznechikError::InvalidPasswordParams);
+        }
+        Ok(PasswordParams { prf, salt, iterations })
+    }
+
+    pub fn prf(&self) -> Prf {
+        self.prf
+    }
+
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// Выработка 256-битного ключа / Derives a 256-bit key
+    pub fn derive_key(&self, password: &[u8]) -> Block256 {
+        let mut key: Block256 = [0u8; 32];
+        match self.prf {
+            Prf::HmacStreebog512 => pbkdf2::<Streebog512>(password, &self.salt, self.iterations, &mut key),
+            Prf::HmacStreebog256 => pbkdf2::<Streebog256>(password, &self.salt, self.iterations, &mut key),
+        }
+        key
+    }
+
+    /// Формат / Layout: prf (1 байт) || iterations (4 байта, BE) || длина соли (2 байта, BE) || соль
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(7 + self.salt.len());
+        result.push(self.prf.id());
+        result.extend_from_slice(&self.iterations.to_be_bytes());
+        result.extend_from_slice(&(self.salt.len() as u16).to_be_bytes());
+        result.extend_from_slice(&self.salt);
+        result
+    }
+
+    /// Разбор параметров в начале `data`; возвращает оставшиеся данные
+    /// Parses the params at the start of `data` and returns the remaining bytes
+    pub fn from_bytes(data: &[u8]) -> Result<(Self, &[u8]), KuznechikError> {
+        if data.len() < 7 {
+            return Err(KuznechikError::InvalidPasswordParams);
+        }
+
+        let prf = Prf::from_id(data[0])?;
+        let iterations = u32::from_be_bytes([data[1], data[2], data[3], data[4]]);
+        let salt_len = u16::from_be_bytes([data[5], data[6]]) as usize;
+        let rest = &data[7..];
+        if rest.len() < salt_len {
+            return Err(KuznechikError::InvalidPasswordParams);
+        }
+
+        let params = Self::new(rest[..salt_len].to_vec(), iterations, prf)?;
+        Ok((params, &rest[salt_len..]))
+    }
+}
+
+/// PBKDF2(P, S, c, dkLen) с псевдослучайной функцией HMAC на основе `H`, длина результата - `out.len()`
+pub fn pbkdf2<H: Digest>(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    let prf = Hmac::<H>::new(password);
+
+    for (i, chunk) in out.chunks_mut(H::OUTPUT_SIZE).enumerate() {
+        let mut hmac = prf.clone();
+        hmac.update(salt);
+        hmac.update(&(i as u32 + 1).to_be_bytes());
+        let mut u = hmac.finalize();
+        let mut t = u;
+
+        for _ in 1..iterations {
+            let mut hmac = prf.clone();
+            hmac.update(u.as_ref());
+            u = hmac.finalize();
+            for (x, y) in t.as_mut().iter_mut().zip(u.as_ref()) {
+                *x ^= y;
+            }
+        }
+
+        chunk.copy_from_slice(&t.as_ref()[..chunk.len()]);
+        u.as_mut().zeroize();
+        t.as_mut().zeroize();
+    }
+}
@@ -0,0 +1,132 @@
+#![cfg(test)]
+
+use super::pbkdf2;
+#[cfg(feature = "alloc")]
+use super::{PasswordParams, Prf};
+use crate::streebog::Streebog512;
+#[cfg(feature = "alloc")]
+use crate::{KeyStore, MagmaKeyStore, KuznechikError};
+
+// Контрольные примеры Р 50.1.111-2016 (PBKDF2 с HMAC_GOSTR3411_2012_512)
+
+#[test]
+fn pbkdf2_streebog512_c1() {
+    let expected = [
+        0x64, 0x77, 0x0a, 0xf7, 0xf7, 0x48, 0xc3, 0xb1, 0xc9, 0xac, 0x83, 0x1d, 0xbc, 0xfd, 0x85, 0xc2,
+        0x61, 0x11, 0xb3, 0x0a, 0x8a, 0x65, 0x7d, 0xdc, 0x30, 0x56, 0xb8, 0x0c, 0xa7, 0x3e, 0x04, 0x0d,
+        0x28, 0x54, 0xfd, 0x36, 0x81, 0x1f, 0x6d, 0x82, 0x5c, 0xc4, 0xab, 0x66, 0xec, 0x0a, 0x68, 0xa4,
+        0x90, 0xa9, 0xe5, 0xcf, 0x51, 0x56, 0xb3, 0xa2, 0xb7, 0xee, 0xcd, 0xdb, 0xf9, 0xa1, 0x6b, 0x47,
+    ];
+
+    let mut dk = [0u8; 64];
+    pbkdf2::<Streebog512>(b"password", b"salt", 1, &mut dk);
+
+    assert_eq!(dk, expected);
+}
+
+#[test]
+fn pbkdf2_streebog512_c2() {
+    let expected = [
+        0x5a, 0x58, 0x5b, 0xaf, 0xdf, 0xbb, 0x6e, 0x88, 0x30, 0xd6, 0xd6, 0x8a, 0xa3, 0xb4, 0x3a, 0xc0,
+        0x0d, 0x2e, 0x4a, 0xeb, 0xce, 0x01, 0xc9, 0xb3, 0x1c, 0x2c, 0xae, 0xd5, 0x6f, 0x02, 0x36, 0xd4,
+        0xd3, 0x4b, 0x2b, 0x8f, 0xbd, 0x2c, 0x4e, 0x89, 0xd5, 0x4d, 0x46, 0xf5, 0x0e, 0x47, 0xd4, 0x5b,
+        0xba, 0xc3, 0x01, 0x57, 0x17, 0x43, 0x11, 0x9e, 0x8d, 0x3c, 0x42, 0xba, 0x66, 0xd3, 0x48, 0xde,
+    ];
+
+    let mut dk = [0u8; 64];
+    pbkdf2::<Streebog512>(b"password", b"salt", 2, &mut dk);
+
+    assert_eq!(dk, expected);
+}
+
+#[test]
+fn pbkdf2_streebog512_long() {
+    let expected = [
+        0xb2, 0xd8, 0xf1, 0x24, 0x5f, 0xc4, 0xd2, 0x92, 0x74, 0x80, 0x20, 0x57, 0xe4, 0xb5, 0x4e, 0x0a,
+        0x07, 0x53, 0xaa, 0x22, 0xfc, 0x53, 0x76, 0x0b, 0x30, 0x1c, 0xf0, 0x08, 0x67, 0x9e, 0x58, 0xfe,
+        0x4b, 0xee, 0x9a, 0xdd, 0xca, 0xe9, 0x9b, 0xa2, 0xb0, 0xb2, 0x0f, 0x43, 0x1a, 0x9c, 0x5e, 0x50,
+        0xf3, 0x95, 0xc8, 0x93, 0x87, 0xd0, 0x94, 0x5a, 0xed, 0xec, 0xa6, 0xeb, 0x40, 0x15, 0xdf, 0xc2,
+        0xbd, 0x24, 0x21, 0xee, 0x9b, 0xb7, 0x11, 0x83, 0xba, 0x88, 0x2c, 0xee, 0xbf, 0xef, 0x25, 0x9f,
+        0x33, 0xf9, 0xe2, 0x7d, 0xc6, 0x17, 0x8c, 0xb8, 0x9d, 0xc3, 0x74, 0x28, 0xcf, 0x9c, 0xc5, 0x2a,
+        0x2b, 0xaa, 0x2d, 0x3a,
+    ];
+
+    let mut dk = [0u8; 100];
+    pbkdf2::<Streebog512>(b"passwordPASSWORDpassword", b"saltSALTsaltSALTsaltSALTsaltSALTsalt", 4096, &mut dk);
+
+    assert_eq!(dk, expected);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn password_params_roundtrip() {
+    // Assign
+    let params = PasswordParams::new(b"saltSALT".to_vec(), 2, Prf::HmacStreebog512).unwrap();
+    let mut data = params.to_bytes();
+    data.extend_from_slice(b"ciphertext");
+
+    // Act
+    let (parsed, rest) = PasswordParams::from_bytes(&data).unwrap();
+
+    // Assert
+    assert_eq!(parsed, params);
+    assert_eq!(rest, b"ciphertext");
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn password_params_invalid() {
+    // Assign
+    let valid = PasswordParams::new(b"salt".to_vec(), 1, Prf::HmacStreebog256).unwrap().to_bytes();
+    let mut unknown_prf = valid.clone();
+    unknown_prf[0] = 0xff;
+
+    // Act & Assert
+    assert_eq!(PasswordParams::new(vec![], 1, Prf::HmacStreebog512).err(), Some(KuznechikError::InvalidPasswordParams));
+    assert_eq!(PasswordParams::new(b"salt".to_vec(), 0, Prf::HmacStreebog512).err(), Some(KuznechikError::InvalidPasswordParams));
+    assert_eq!(PasswordParams::from_bytes(&valid[..valid.len() - 1]).err(), Some(KuznechikError::InvalidPasswordParams));
+    assert_eq!(PasswordParams::from_bytes(&unknown_prf).err(), Some(KuznechikError::InvalidPasswordParams));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn with_password_params() {
+    // Assign
+    let params = PasswordParams::new(b"salt".to_vec(), 1, Prf::HmacStreebog512).unwrap();
+    let mut dk = [0u8; 64];
+    pbkdf2::<Streebog512>(b"password", b"salt", 1, &mut dk);
+    let mut master_key = [0u8; 32];
+    master_key.copy_from_slice(&dk[..32]);
+
+    // Act
+    let kuz = KeyStore::with_password_and_params(b"password", &params);
+    let magma = MagmaKeyStore::with_password_and_params(b"password", &params);
+    let other_salt = KeyStore::with_password_and_params(
+        b"password",
+        &PasswordParams::new(b"pepper".to_vec(), 1, Prf::HmacStreebog512).unwrap(),
+    );
+
+    // Assert
+    assert!(kuz.keys == KeyStore::with_master_key(master_key).keys);
+    assert_eq!(magma.keys, MagmaKeyStore::with_master_key(master_key).keys);
+    assert!(kuz.keys != other_salt.keys);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn with_password_params_checks_params() {
+    // Assign
+    let params = PasswordParams::new(b"salt".to_vec(), 2, Prf::HmacStreebog256).unwrap();
+
+    // Act
+    let kuz = KeyStore::with_password_params(b"password", b"salt", 2, Prf::HmacStreebog256).unwrap();
+    let magma = MagmaKeyStore::with_password_params(b"password", b"salt", 2, Prf::HmacStreebog256).unwrap();
+    let empty_salt = KeyStore::with_password_params(b"password", b"", 2, Prf::HmacStreebog512).err();
+    let no_iterations = MagmaKeyStore::with_password_params(b"password", b"salt", 0, Prf::HmacStreebog512).err();
+
+    // Assert
+    assert!(kuz.keys == KeyStore::with_password_and_params(b"password", &params).keys);
+    assert_eq!(magma.keys, MagmaKeyStore::with_password_and_params(b"password", &params).keys);
+    assert_eq!(empty_salt, Some(KuznechikError::InvalidPasswordParams));
+    assert_eq!(no_iterations, Some(KuznechikError::InvalidPasswordParams));
+}
@@ -1,9 +1,17 @@
 mod tests;
 
-use std::convert::TryInto;
+use core::convert::TryInto;
+use core::fmt;
+
+use zeroize::Zeroize;
 
 use crate::types::{Block128, Block256};
-use crate::transforms::{tfm_c, tfm_f};
+use crate::transforms::{tfm_c, tfm_f, encrypt_block, decrypt_block, bitsliced, ls_tables, simd};
+use crate::algorithms::{BlockCipher, MasterKeyInit, for_each_chunk};
+#[cfg(feature = "alloc")]
+use crate::kdf::{PasswordParams, Prf};
+#[cfg(feature = "alloc")]
+use crate::error::KuznechikError;
 
 /**
 * # Алгоритм синхронного шифрования "Кузнечик" (ГОСТ Р 34.12-2015, ГОСТ Р 34.13-2015)<br>Synchronous encryption algorithm "Kuznechik" (GOST R 34.12-2015, GOST R 34.13-2015)
@@ -11,10 +19,14 @@ use crate::transforms::{tfm_c, tfm_f};
 * ## Режимы шифрования / Encryption modes:
 * AlgEcb - режим простой замены / Electronic Codebook (ЕСВ)
 * AlgCtr - режим гаммирования / Counter (CTR)
+* AlgCtrAcpkm - режим гаммирования с внутренней сменой ключа / Counter with ACPKM re-keying (CTR-ACPKM)
 * AlgOfb - режим гаммирования с обратной связью по выходу / Output Feedback (OFB)
 * AlgCbc - режим простой замены с зацеплением / Cipher Block Chaining (СВС)
 * AlgCfb - режим гаммирования с обратной связью по шифртексту / Cipher Feedback, (CFB)
 * AlgMac - режим выработки имитовставки / Message Authentication Code (MAC)
+* AlgMacAcpkm - режим выработки имитовставки с внутренней сменой ключа / MAC with ACPKM-Master re-keying (OMAC-ACPKM)
+* AlgMgm - режим аутентифицированного шифрования / Multilinear Galois Mode (MGM)
+* CtrOmac - гаммирование с последующей выработкой имитовставки / Encrypt-then-MAC (CTR + OMAC)
 *
 * # Использование / Usage (AlgOfb):
 * ```
@@ -64,12 +76,105 @@ use crate::transforms::{tfm_c, tfm_f};
 *
 * assert_eq!(dec_data, data);
 * ```
+*
+* Ключи затираются при удалении и повторной установке, `Debug` их не выводит, `Clone` не реализован.
+* Key material is wiped on drop and on re-keying, `Debug` is redacted and `KeyStore` is not `Clone`.
 **/
 
 #[derive(Default)]
 pub struct KeyStore {
     pub(crate) keys: [Block128; 10],
+    /// Ключи расшифрования по таблицам: K_1, L^-1(K_2), ..., L^-1(K_10); нули для `ConstantTime` и `Reference`
+    pub(crate) dec_keys: [Block128; 10],
     master_key: Block256,
+    backend: Backend,
+}
+
+///
+/// Реализация преобразований шифра / Implementation of the cipher transforms
+///
+/// `Table` зашифровывает по таблицам LS (16 обращений к таблицам на раунд), `Reference` - эталонная реализация
+/// преобразований S и L по ГОСТ, оставленная для сверки. Обе обращаются к таблицам по секретным индексам и поэтому
+/// уязвимы к атакам по времени доступа к кэшу. `ConstantTime` не обращается к памяти по секретным индексам, но работает медленнее.
+/// `Table` encrypts with precomputed LS tables, `Reference` follows the standard step by step and is kept for cross-checking;
+/// both index tables with secret bytes and are exposed to cache-timing attacks.
+/// `ConstantTime` has no secret-dependent memory access but is slower.
+/// `Simd` обрабатывает по 16/32 блока командами SSSE3/AVX2 в режимах ECB и CTR (без секретных индексов), одиночные блоки - по таблицам;
+/// набор команд определяется во время выполнения, без него используется `Table`.
+/// `Simd` runs ECB and CTR 16 or 32 blocks at a time with SSSE3/AVX2 and single blocks through the tables;
+/// the CPU is probed at run time and `Table` is used when the instructions are missing.
+/// По умолчанию выбирается `Simd`: на процессоре без SSSE3 он совпадает с `Table`, а ECB и CTR ускоряются без изменения результата.
+/// `Simd` is the default, so every caller gets the vector path for ECB and CTR when the CPU has it.
+/// С функцией `constant-time` по умолчанию выбирается `ConstantTime` / The `constant-time` feature makes `ConstantTime` the default.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Table,
+    Reference,
+    ConstantTime,
+    Simd,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        if cfg!(feature = "constant-time") {
+            Backend::ConstantTime
+        } else {
+            Backend::Simd
+        }
+    }
+}
+
+impl Backend {
+    pub(crate) fn encrypt_block(self, data: &mut Block128, keys: &[Block128; 10]) {
+        match self {
+            Backend::Table => ls_tables::encrypt_block(data, keys),
+            Backend::Reference => encrypt_block(data, keys),
+            Backend::ConstantTime => bitsliced::encrypt_block(data, keys),
+            Backend::Simd => simd::encrypt_block(data, keys),
+        }
+    }
+
+    pub(crate) fn decrypt_block(self, data: &mut Block128, keys: &[Block128; 10], dec_keys: &[Block128; 10]) {
+        match self {
+            Backend::Table => ls_tables::decrypt_block(data, dec_keys),
+            Backend::Reference => decrypt_block(data, keys),
+            Backend::ConstantTime => bitsliced::decrypt_block(data, keys),
+            Backend::Simd => simd::decrypt_block(data, dec_keys),
+        }
+    }
+
+    /// Подряд идущие блоки, `data.len()` кратна 16
+    pub(crate) fn encrypt_blocks(self, data: &mut [u8], keys: &[Block128; 10]) {
+        match self {
+            Backend::Simd => simd::encrypt_blocks(data, keys),
+            _ => for chunk in data.chunks_exact_mut(16) {
+                self.encrypt_block(chunk.try_into().unwrap(), keys);
+            },
+        }
+    }
+
+    pub(crate) fn decrypt_blocks(self, data: &mut [u8], keys: &[Block128; 10], dec_keys: &[Block128; 10]) {
+        match self {
+            Backend::Simd => simd::decrypt_blocks(data, keys, dec_keys),
+            _ => for chunk in data.chunks_exact_mut(16) {
+                self.decrypt_block(chunk.try_into().unwrap(), keys, dec_keys);
+            },
+        }
+    }
+
+    /// Нужны ли ключи L^-1(K_i); `ConstantTime` и `Reference` их не используют, и таблицы не затрагиваются
+    /// Whether the backend decrypts with L^-1(K_i); they are computed with secret-indexed tables
+    fn uses_dec_keys(self) -> bool {
+        matches!(self, Backend::Table | Backend::Simd)
+    }
+
+    fn tfm_f(self, data: &mut Block256, key: &Block128) {
+        match self {
+            Backend::Table | Backend::Reference | Backend::Simd => tfm_f(data, key),
+            Backend::ConstantTime => bitsliced::tfm_f(data, key),
+        }
+    }
 }
 
 impl KeyStore {
@@ -77,6 +182,42 @@ impl KeyStore {
         Self::default()
     }
 
+    ///
+    /// Пустое хранилище с заданной реализацией; ключ устанавливается следующим вызовом, и его развёртка
+    /// сразу выполняется этой реализацией / An empty store with the given backend, so the key schedule runs on it too
+    ///
+    /// ```
+    /// use kuznechik::{KeyStore, Backend};
+    ///
+    /// let kuz = KeyStore::with_backend(Backend::ConstantTime).master_key([0x11; 32]);
+    /// assert_eq!(kuz.get_backend(), Backend::ConstantTime);
+    /// ```
+    ///
+    pub fn with_backend(backend: Backend) -> Self {
+        let mut instance = Self::new();
+        instance.backend = backend;
+        instance
+    }
+
+    pub fn master_key(mut self, master_key: Block256) -> Self {
+        self.set_master_key(master_key);
+        self
+    }
+
+    pub fn password(mut self, password: &str) -> Self {
+        self.set_password(password);
+        self
+    }
+
+    /// Ключ из пароля по PBKDF2 / Key derived from the password with PBKDF2
+    #[cfg(feature = "alloc")]
+    pub fn password_params(mut self, password: &[u8], params: &PasswordParams) -> Self {
+        let mut master_key = params.derive_key(password);
+        self.set_master_key(master_key);
+        master_key.zeroize();
+        self
+    }
+
     pub fn with_password(password: &str) -> Self {
         let mut instance = Self::new();
         instance.set_password(password);
@@ -89,9 +230,28 @@ impl KeyStore {
         instance
     }
 
+    ///
+    /// Ключ из пароля по PBKDF2 (Р 50.1.111-2016) / Key derived from the password with PBKDF2 (R 50.1.111-2016)
+    ///
+    /// Пустая соль, соль длиннее 65535 байт или ноль итераций - `InvalidPasswordParams`.
+    /// To derive the same key again, store `PasswordParams::new(salt, iterations, prf)` with the ciphertext.
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn with_password_params(password: &[u8], salt: &[u8], iterations: u32, prf: Prf) -> Result<Self, KuznechikError> {
+        let params = PasswordParams::new(salt.to_vec(), iterations, prf)?;
+        Ok(Self::with_password_and_params(password, &params))
+    }
+
+    /// Ключ из пароля по сохранённым параметрам PBKDF2 / Key derived from the password with stored PBKDF2 params
+    #[cfg(feature = "alloc")]
+    pub fn with_password_and_params(password: &[u8], params: &PasswordParams) -> Self {
+        Self::new().password_params(password, params)
+    }
+
     pub fn set_password(&mut self, password: &str) {
-        self.master_key = Self::hash_password(password);
-        self.expand_key();
+        let mut master_key = Self::hash_password(password);
+        self.set_master_key(master_key);
+        master_key.zeroize();
     }
 
     pub fn set_master_key(&mut self, master_key: Block256) {
@@ -99,19 +259,49 @@ impl KeyStore {
         self.expand_key();
     }
 
-    fn hash_password(password: &str) -> Block256 {
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.set_backend(backend);
+        self
+    }
+
+    /// Смена реализации; развёртка ключа выполняется заново выбранной реализацией, но прежняя уже обработала ключ.
+    /// Чтобы и развёртка шла за постоянное время, используйте [`KeyStore::with_backend`].
+    /// Re-expands the key with the new backend. The previous backend has already processed the key,
+    /// so use [`KeyStore::with_backend`] to keep the key schedule constant-time as well.
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+        self.expand_key();
+    }
+
+    pub fn get_backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub(crate) fn hash_password(password: &str) -> Block256 {
         use sha3::{Digest, Sha3_256};
 
+        // С функцией `zeroize` крейта sha3 состояние Keccak затирается при удалении hasher;
+        // буфер неполного блока в digest 0.10 не затирается / digest 0.10 does not wipe its partial-block buffer
         let mut hasher = Sha3_256::new();
         hasher.update(password);
+        let mut hash = hasher.finalize();
+
+        let result = hash.as_slice().try_into()
+            .expect("hash_password(): into Block256");
+        hash.as_mut_slice().zeroize();
+        result
+    }
 
-        hasher.finalize().as_slice().try_into()
-            .expect("hash_password(): into Block256")
+    /// Затирает ключ и раундовые ключи / Wipes the master key and the round keys
+    pub(crate) fn wipe(&mut self) {
+        self.keys.zeroize();
+        self.dec_keys.zeroize();
+        self.master_key.zeroize();
     }
 
     fn expand_key(&mut self) {
         let mut c: Block128 = [0u8; 16];
-        let mut const_c: Block256 = self.master_key.clone();            // Iterative constants
+        let mut const_c: Block256 = self.master_key;            // Iterative constants
 
         self.keys[0].copy_from_slice(&self.master_key[..16]);       // Key 1
         self.keys[1].copy_from_slice(&self.master_key[16..]);       // Key 2
@@ -120,7 +310,7 @@ impl KeyStore {
         for j in 0..4 {
             for i in 1..9 {
                 tfm_c(&mut c, j * 8 + i);
-                tfm_f(&mut const_c, &c);
+                self.backend.tfm_f(&mut const_c, &c);
             }
 
             self.keys[k].copy_from_slice(&const_c[..16]);           // Key 3, 5, 7, 9
@@ -128,5 +318,56 @@ impl KeyStore {
             self.keys[k].copy_from_slice(&const_c[16..]);           // Key 4, 6, 8, 10
             k += 1;
         }
+
+        c.zeroize();
+        const_c.zeroize();
+
+        self.dec_keys.zeroize();
+        if self.backend.uses_dec_keys() {
+            self.dec_keys = ls_tables::decryption_keys(&self.keys);
+        }
+    }
+}
+
+impl Drop for KeyStore {
+    fn drop(&mut self) {
+        self.wipe();
+    }
+}
+
+impl fmt::Debug for KeyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("KeyStore { ... }")
+    }
+}
+
+impl BlockCipher for KeyStore {
+    type Block = Block128;
+    const BLOCK_SIZE: usize = 16;
+
+    fn encrypt_block(&self, block: &mut Block128) {
+        self.backend.encrypt_block(block, &self.keys);
+    }
+
+    fn decrypt_block(&self, block: &mut Block128) {
+        self.backend.decrypt_block(block, &self.keys, &self.dec_keys);
+    }
+
+    fn encrypt_blocks(&self, data: &mut [u8]) {
+        for_each_chunk(data, |chunk| self.backend.encrypt_blocks(chunk, &self.keys));
+    }
+
+    fn decrypt_blocks(&self, data: &mut [u8]) {
+        for_each_chunk(data, |chunk| self.backend.decrypt_blocks(chunk, &self.keys, &self.dec_keys));
+    }
+}
+
+impl MasterKeyInit for KeyStore {
+    fn from_master_key(master_key: Block256) -> Self {
+        Self::with_master_key(master_key)
+    }
+
+    fn rekeyed(&self, master_key: Block256) -> Self {
+        Self::with_backend(self.backend).master_key(master_key)
     }
 }
@@ -1,6 +1,6 @@
 #![cfg(test)]
 
-use super::KeyStore;
+use super::{KeyStore, Backend};
 
 #[test]
 fn expand_key() {
@@ -22,3 +22,62 @@ fn expand_key() {
     assert_eq!(&kuz.keys[8], &[0xbb, 0x44, 0xe2, 0x53, 0x78, 0xc7, 0x31, 0x23, 0xa5, 0xf3, 0x2f, 0x73, 0xcd, 0xb6, 0xe5, 0x17]);
     assert_eq!(&kuz.keys[9], &[0x72, 0xe9, 0xdd, 0x74, 0x16, 0xbc, 0xf4, 0x5b, 0x75, 0x5d, 0xba, 0xa8, 0x8e, 0x4a, 0x40, 0x43]);
 }
+
+#[test]
+fn debug_is_redacted() {
+    let kuz = KeyStore::with_password("Kuznechik");
+
+    assert_eq!(format!("{:?}", kuz), "KeyStore { ... }");
+}
+
+#[test]
+fn wipe_clears_keys() {
+    let mut kuz = KeyStore::with_password("Kuznechik");
+
+    kuz.wipe();
+
+    assert!(kuz.keys.iter().all(|key| key == &[0u8; 16]));
+    assert!(kuz.dec_keys.iter().all(|key| key == &[0u8; 16]));
+    assert_eq!(kuz.master_key, [0u8; 32]);
+}
+
+#[test]
+fn with_backend_sets_key_afterwards() {
+    let kuz = KeyStore::with_backend(Backend::ConstantTime).master_key([0x11; 32]);
+    let password = KeyStore::with_backend(Backend::Reference).password("Kuznechik");
+
+    assert_eq!(kuz.get_backend(), Backend::ConstantTime);
+    assert!(kuz.keys == KeyStore::with_master_key([0x11; 32]).keys);
+    assert_eq!(password.get_backend(), Backend::Reference);
+    assert!(password.keys == KeyStore::with_password("Kuznechik").keys);
+}
+
+#[test]
+fn constant_time_skips_table_decryption_keys() {
+    let ct = KeyStore::with_backend(Backend::ConstantTime).master_key([0x11; 32]);
+    let reference = KeyStore::with_backend(Backend::Reference).master_key([0x11; 32]);
+    let mut switched = KeyStore::with_backend(Backend::ConstantTime).master_key([0x11; 32]);
+    switched.set_backend(Backend::Table);
+    switched.set_backend(Backend::ConstantTime);
+
+    // Ключи L^-1(K_i) вычисляются по таблицам с секретными индексами, поэтому их не должно быть
+    assert!(ct.dec_keys.iter().all(|key| key == &[0u8; 16]));
+    assert!(reference.dec_keys.iter().all(|key| key == &[0u8; 16]));
+    assert!(switched.dec_keys.iter().all(|key| key == &[0u8; 16]));
+    assert!(KeyStore::with_backend(Backend::Table).master_key([0x11; 32]).dec_keys[1] != [0u8; 16]);
+}
+
+#[test]
+fn default_backend_uses_simd() {
+    // Assign
+    let expected = if cfg!(feature = "constant-time") { Backend::ConstantTime } else { Backend::Simd };
+
+    // Act
+    let kuz = KeyStore::with_master_key([0x22; 32]);
+    let table = KeyStore::with_backend(Backend::Table).master_key([0x22; 32]);
+
+    // Assert
+    assert_eq!(Backend::default(), expected);
+    assert_eq!(kuz.get_backend(), expected);
+    assert!(kuz.keys == table.keys);
+}
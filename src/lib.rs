@@ -1,12 +1,36 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 extern crate sha3;
 
 mod types;
+mod error;
 mod tables;
 mod transforms;
 mod algorithms;
 mod key_store;
+mod magma;
+mod streebog;
+mod hmac;
+mod kdf;
+#[cfg(feature = "alloc")]
+mod container;
+#[cfg(feature = "cipher")]
+mod rust_crypto;
 mod tests;
 
 pub use types::*;
+pub use error::*;
 pub use key_store::*;
+pub use magma::MagmaKeyStore;
+pub use streebog::{Digest, Streebog256, Streebog512};
+pub use hmac::{Hmac, HmacStreebog256, HmacStreebog512};
+pub use kdf::{pbkdf2, Prf};
+#[cfg(feature = "alloc")]
+pub use kdf::PasswordParams;
 pub use algorithms::*;
+#[cfg(feature = "alloc")]
+pub use container::{ContainerReader, ContainerWriter};
+#[cfg(feature = "cipher")]
+pub use rust_crypto::Kuznechik128;
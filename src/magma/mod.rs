@@ -0,0 +1,206 @@
+mod tests;
+
+use core::convert::TryInto;
+use core::fmt;
+
+use zeroize::Zeroize;
+
+use crate::types::{Block64, Block256};
+use crate::tables::MAGMA_PI;
+use crate::algorithms::{BlockCipher, MasterKeyInit, for_each_chunk};
+#[cfg(feature = "alloc")]
+use crate::kdf::{PasswordParams, Prf};
+#[cfg(feature = "alloc")]
+use crate::error::KuznechikError;
+
+/**
+* # Алгоритм блочного шифрования "Магма" (ГОСТ Р 34.12-2015, RFC 8891)<br>Block cipher "Magma" (GOST R 34.12-2015, RFC 8891)
+*
+* Блок 64 бита, ключ 256 бит. Реализует [`BlockCipher`], поэтому используется со всеми режимами.
+* 64-bit block, 256-bit key. Implements [`BlockCipher`], so every mode works with it.
+*
+* # Использование / Usage (AlgEcb):
+* ```
+* use crate::kuznechik::{MagmaKeyStore, Kuznechik, AlgEcb};
+*
+* let master_key = [
+*     0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00,
+*     0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff,
+* ];
+*
+* let magma = MagmaKeyStore::with_master_key(master_key);
+* let mut cipher = AlgEcb::new(&magma);
+*
+* let data = Vec::from("Hello, World!");
+* let enc_data = cipher.encrypt(data.clone());
+*
+* assert_eq!(enc_data.len(), 16);
+* assert_eq!(cipher.decrypt(enc_data), data);
+* ```
+**/
+
+#[derive(Default)]
+pub struct MagmaKeyStore {
+    pub(crate) keys: [u32; 8],
+    master_key: Block256,
+}
+
+impl MagmaKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_password(password: &str) -> Self {
+        let mut instance = Self::new();
+        instance.set_password(password);
+        instance
+    }
+
+    pub fn with_master_key(master_key: Block256) -> Self {
+        let mut instance = Self::new();
+        instance.set_master_key(master_key);
+        instance
+    }
+
+    /// Ключ из пароля по PBKDF2 (Р 50.1.111-2016) / Key derived from the password with PBKDF2 (R 50.1.111-2016)
+    #[cfg(feature = "alloc")]
+    pub fn with_password_params(password: &[u8], salt: &[u8], iterations: u32, prf: Prf) -> Result<Self, KuznechikError> {
+        let params = PasswordParams::new(salt.to_vec(), iterations, prf)?;
+        Ok(Self::with_password_and_params(password, &params))
+    }
+
+    /// Ключ из пароля по сохранённым параметрам PBKDF2 / Key derived from the password with stored PBKDF2 params
+    #[cfg(feature = "alloc")]
+    pub fn with_password_and_params(password: &[u8], params: &PasswordParams) -> Self {
+        let mut master_key = params.derive_key(password);
+        let instance = Self::with_master_key(master_key);
+        master_key.zeroize();
+        instance
+    }
+
+    pub fn set_password(&mut self, password: &str) {
+        let mut master_key = crate::key_store::KeyStore::hash_password(password);
+        self.set_master_key(master_key);
+        master_key.zeroize();
+    }
+
+    pub fn set_master_key(&mut self, master_key: Block256) {
+        self.master_key = master_key;
+        self.expand_key();
+    }
+
+    /// Ключи K1..K8 - 32-битные слова ключа, начиная со старшего
+    fn expand_key(&mut self) {
+        for (i, key) in self.keys.iter_mut().enumerate() {
+            *key = u32::from_be_bytes(self.master_key[4*i..4*(i+1)].try_into().unwrap());
+        }
+    }
+}
+
+impl Drop for MagmaKeyStore {
+    fn drop(&mut self) {
+        self.keys.zeroize();
+        self.master_key.zeroize();
+    }
+}
+
+impl fmt::Debug for MagmaKeyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MagmaKeyStore { ... }")
+    }
+}
+
+impl BlockCipher for MagmaKeyStore {
+    type Block = Block64;
+    const BLOCK_SIZE: usize = 8;
+
+    fn encrypt_block(&self, block: &mut Block64) {
+        encrypt_block(block, &self.keys);
+    }
+
+    fn decrypt_block(&self, block: &mut Block64) {
+        decrypt_block(block, &self.keys);
+    }
+
+    fn encrypt_blocks(&self, data: &mut [u8]) {
+        for_each_chunk(data, |chunk| for block in chunk.chunks_exact_mut(8) {
+            encrypt_block(block.try_into().unwrap(), &self.keys);
+        });
+    }
+
+    fn decrypt_blocks(&self, data: &mut [u8]) {
+        for_each_chunk(data, |chunk| for block in chunk.chunks_exact_mut(8) {
+            decrypt_block(block.try_into().unwrap(), &self.keys);
+        });
+    }
+}
+
+/// Итерационные ключи: K1..K8 трижды, затем K8..K1
+#[inline]
+fn round_key(keys: &[u32; 8], round: usize) -> u32 {
+    match round {
+        0..=23 => keys[round % 8],
+        _ => keys[31 - round],
+    }
+}
+
+#[inline]
+pub(crate) fn encrypt_block(data: &mut Block64, keys: &[u32; 8]) {
+    let (mut a1, mut a0) = split(data);
+    for round in 0..32 {
+        tfm_g(&mut a1, &mut a0, round_key(keys, round));
+    }
+    join(data, a0, a1);
+}
+
+#[inline]
+pub(crate) fn decrypt_block(data: &mut Block64, keys: &[u32; 8]) {
+    let (mut a1, mut a0) = split(data);
+    for round in (0..32).rev() {
+        tfm_g(&mut a1, &mut a0, round_key(keys, round));
+    }
+    join(data, a0, a1);
+}
+
+#[inline]
+fn split(data: &Block64) -> (u32, u32) {
+    (u32::from_be_bytes(data[..4].try_into().unwrap()), u32::from_be_bytes(data[4..].try_into().unwrap()))
+}
+
+/// Последний раунд G* не меняет половины местами, поэтому они записываются в обратном порядке
+#[inline]
+fn join(data: &mut Block64, a1: u32, a0: u32) {
+    data[..4].copy_from_slice(&a1.to_be_bytes());
+    data[4..].copy_from_slice(&a0.to_be_bytes());
+}
+
+/// Преобразование G[k](a1, a0) = (a0, g[k](a0) ⊕ a1)
+#[inline]
+fn tfm_g(a1: &mut u32, a0: &mut u32, key: u32) {
+    let temp = *a0;
+    *a0 = tfm_small_g(*a0, key) ^ *a1;
+    *a1 = temp;
+}
+
+/// Преобразование g[k](a) = (t(a ⊞ k)) <<< 11
+#[inline]
+fn tfm_small_g(a: u32, key: u32) -> u32 {
+    tfm_t(a.wrapping_add(key)).rotate_left(11)
+}
+
+/// Подстановка t: i-я тетрада заменяется по таблице πi
+#[inline]
+fn tfm_t(a: u32) -> u32 {
+    let mut res = 0u32;
+    for (i, pi) in MAGMA_PI.iter().enumerate() {
+        let nibble = (a >> (4 * i)) & 0x0f;
+        res |= (pi[nibble as usize] as u32) << (4 * i);
+    }
+    res
+}
+
+impl MasterKeyInit for MagmaKeyStore {
+    fn from_master_key(master_key: Block256) -> Self {
+        Self::with_master_key(master_key)
+    }
+}
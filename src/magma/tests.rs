@@ -0,0 +1,52 @@
+#![cfg(test)]
+
+use super::{MagmaKeyStore, encrypt_block, decrypt_block, tfm_t, tfm_small_g};
+
+const MASTER_KEY: [u8; 32] = [
+    0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00,
+    0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff,
+];
+
+#[test]
+fn test_transform_t() {
+    assert_eq!(tfm_t(0xfdb97531), 0x2a196f34);
+    assert_eq!(tfm_t(0x2a196f34), 0xebd9f03a);
+    assert_eq!(tfm_t(0xebd9f03a), 0xb039bb3d);
+    assert_eq!(tfm_t(0xb039bb3d), 0x68695433);
+}
+
+#[test]
+fn test_transform_g() {
+    assert_eq!(tfm_small_g(0xfedcba98, 0x87654321), 0xfdcbc20c);
+    assert_eq!(tfm_small_g(0x87654321, 0xfdcbc20c), 0x7e791a4b);
+    assert_eq!(tfm_small_g(0xfdcbc20c, 0x7e791a4b), 0xc76549ec);
+    assert_eq!(tfm_small_g(0x7e791a4b, 0xc76549ec), 0x9791c849);
+}
+
+#[test]
+fn expand_key() {
+    let magma = MagmaKeyStore::with_master_key(MASTER_KEY);
+
+    assert_eq!(magma.keys, [0xffeeddcc, 0xbbaa9988, 0x77665544, 0x33221100, 0xf0f1f2f3, 0xf4f5f6f7, 0xf8f9fafb, 0xfcfdfeff]);
+}
+
+#[test]
+fn encrypt_decrypt_block() {
+    let magma = MagmaKeyStore::with_master_key(MASTER_KEY);
+
+    let mut data = [0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10];
+    let data_backup = data;
+
+    encrypt_block(&mut data, &magma.keys);
+    assert_eq!(data, [0x4e, 0xe9, 0x01, 0xe5, 0xc2, 0xd8, 0xca, 0x3d]);
+
+    decrypt_block(&mut data, &magma.keys);
+    assert_eq!(data, data_backup);
+}
+
+#[test]
+fn debug_is_redacted() {
+    let magma = MagmaKeyStore::with_master_key(MASTER_KEY);
+
+    assert_eq!(format!("{:?}", magma), "MagmaKeyStore { ... }");
+}
@@ -0,0 +1,88 @@
+mod tests;
+
+use core::fmt;
+use cipher::{AlgorithmName, BlockCipher, Key, KeyInit, KeySizeUser};
+use cipher::consts::{U16, U32};
+use zeroize::Zeroize;
+
+use crate::types::Block128;
+use crate::key_store::{KeyStore, Backend};
+
+///
+/// Блочный шифр "Кузнечик" для экосистемы RustCrypto / Kuznechik block cipher for the RustCrypto ecosystem
+///
+/// Реализует `KeyInit`, `BlockCipher`, `BlockEncrypt` и `BlockDecrypt` из крейта `cipher`,
+/// поэтому может использоваться с крейтами режимов и имитовставок (`cbc`, `ctr`, `cmac`, ...).
+/// Available with the `cipher` feature.
+///
+/// ```
+/// use kuznechik::{KeyStore, Kuznechik128};
+/// use cipher::{BlockEncrypt, BlockDecrypt};
+///
+/// let kuz = KeyStore::with_password("Kuznechik");
+/// let cipher = Kuznechik128::from(&kuz);
+///
+/// let mut block = [0x11u8; 16].into();
+/// cipher.encrypt_block(&mut block);
+/// cipher.decrypt_block(&mut block);
+///
+/// assert_eq!(block, [0x11u8; 16].into());
+/// ```
+///
+#[derive(Clone)]
+pub struct Kuznechik128 {
+    keys: [Block128; 10],
+    dec_keys: [Block128; 10],
+    backend: Backend,
+}
+
+impl From<&KeyStore> for Kuznechik128 {
+    fn from(kuz: &KeyStore) -> Self {
+        Kuznechik128 { keys: kuz.keys, dec_keys: kuz.dec_keys, backend: kuz.get_backend() }
+    }
+}
+
+impl KeySizeUser for Kuznechik128 {
+    type KeySize = U32;
+}
+
+impl KeyInit for Kuznechik128 {
+    fn new(key: &Key<Self>) -> Self {
+        Self::from(&KeyStore::with_master_key((*key).into()))
+    }
+}
+
+impl BlockCipher for Kuznechik128 {}
+
+impl AlgorithmName for Kuznechik128 {
+    fn write_alg_name(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Kuznechik")
+    }
+}
+
+impl Drop for Kuznechik128 {
+    fn drop(&mut self) {
+        self.keys.zeroize();
+        self.dec_keys.zeroize();
+    }
+}
+
+impl fmt::Debug for Kuznechik128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Kuznechik128 { ... }")
+    }
+}
+
+cipher::impl_simple_block_encdec!(
+    Kuznechik128, U16, state, block,
+    encrypt: {
+        let mut data: Block128 = (*block.get_in()).into();
+        state.backend.encrypt_block(&mut data, &state.keys);
+        *block.get_out() = data.into();
+    }
+    decrypt: {
+        let mut data: Block128 = (*block.get_in()).into();
+        state.backend.decrypt_block(&mut data, &state.keys, &state.dec_keys);
+        *block.get_out() = data.into();
+    }
+);
@@ -0,0 +1,93 @@
+#![cfg(test)]
+
+use cipher::{BlockEncrypt, BlockDecrypt, BlockEncryptMut, KeyInit, KeyIvInit, StreamCipher};
+use cmac::Mac;
+use crate::{KeyStore, Kuznechik, AlgEcb, AlgCbc, AlgCtr, AlgMac};
+use super::Kuznechik128;
+
+const MASTER_KEY: [u8; 32] = [
+    0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+    0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+];
+
+const DATA: [u8; 64] = [
+    0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x00, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88,
+    0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xee, 0xff, 0x0a,
+    0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xee, 0xff, 0x0a, 0x00,
+    0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xee, 0xff, 0x0a, 0x00, 0x11,
+];
+
+#[test]
+fn encrypt_decrypt_block() {
+    // Assign
+    let cipher = Kuznechik128::new(&MASTER_KEY.into());
+    let mut block = cipher::Block::<Kuznechik128>::clone_from_slice(&DATA[..16]);
+
+    // Act
+    cipher.encrypt_block(&mut block);
+    let enc_block = block;
+    cipher.decrypt_block(&mut block);
+
+    // Assert
+    assert_eq!(enc_block[..], [0x7f, 0x67, 0x9d, 0x90, 0xbe, 0xbc, 0x24, 0x30, 0x5a, 0x46, 0x8d, 0x42, 0xb9, 0xd4, 0xed, 0xcd]);
+    assert_eq!(block[..], DATA[..16]);
+}
+
+#[test]
+fn cross_check_ecb_cbc() {
+    // Assign
+    let kuz = KeyStore::with_master_key(MASTER_KEY);
+    let iv = [0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xce, 0xf0, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf0, 0x01, 0x12];
+
+    let mut ecb_data = DATA;
+    let mut cbc_data = DATA;
+
+    // Act
+    let cipher = Kuznechik128::from(&kuz);
+    for block in ecb_data.chunks_exact_mut(16) {
+        cipher.encrypt_block(block.into());
+    }
+
+    let mut cbc = cbc::Encryptor::<Kuznechik128>::new(&MASTER_KEY.into(), &iv.into());
+    for block in cbc_data.chunks_exact_mut(16) {
+        cbc.encrypt_block_mut(block.into());
+    }
+
+    // Assert
+    assert_eq!(ecb_data[..], AlgEcb::new(&kuz).encrypt(DATA.to_vec())[..64]);
+    assert_eq!(cbc_data[..], AlgCbc::new(&kuz).gamma(iv.to_vec()).encrypt(DATA.to_vec())[..64]);
+}
+
+#[test]
+fn cross_check_ctr() {
+    // Assign
+    let kuz = KeyStore::with_master_key(MASTER_KEY);
+    let iv = [0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xce, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+    for len in [0, 1, 15, 16, 17, 64] {
+        let mut data = DATA[..len].to_vec();
+
+        // Act
+        let mut ctr = ctr::Ctr128BE::<Kuznechik128>::new(&MASTER_KEY.into(), &iv.into());
+        ctr.apply_keystream(&mut data);
+
+        // Assert
+        assert_eq!(data, AlgCtr::new(&kuz).gamma(iv.to_vec()).encrypt(DATA[..len].to_vec()));
+    }
+}
+
+#[test]
+fn cross_check_mac() {
+    // Assign
+    let kuz = KeyStore::with_master_key(MASTER_KEY);
+
+    for len in [0, 1, 15, 16, 17, 64] {
+        // Act
+        let mut cmac = <cmac::Cmac<Kuznechik128> as KeyInit>::new(&MASTER_KEY.into());
+        cmac.update(&DATA[..len]);
+        let tag = cmac.finalize().into_bytes();
+
+        // Assert
+        assert_eq!(tag[..8], AlgMac::new(&kuz).encrypt(DATA[..len].to_vec())[..]);
+    }
+}
@@ -0,0 +1,257 @@
+mod tests;
+
+use crate::types::{Block256, Block512};
+use crate::tables::{K_PI, STREEBOG_A, STREEBOG_C};
+use crate::transforms::sum_mod_2;
+use zeroize::Zeroize;
+
+/**
+* # Функция хэширования "Стрибог" (ГОСТ Р 34.11-2012, RFC 6986)<br>Hash function "Streebog" (GOST R 34.11-2012, RFC 6986)
+*
+* [`Streebog256`] и [`Streebog512`] принимают данные частями через `update`, результат возвращает `finalize`.
+* Байты сообщения и хэш-кода идут в порядке от младшего к старшему, как в эталонной реализации
+* (RFC 6986 записывает их в обратном порядке).
+* Message and digest bytes are little-endian, as in the reference implementation (RFC 6986 prints them reversed).
+*
+* # Использование / Usage:
+* ```
+* use crate::kuznechik::Streebog256;
+*
+* let mut hasher = Streebog256::new();
+* hasher.update(b"Hello, ");
+* hasher.update(b"World!");
+*
+* assert_eq!(hasher.finalize(), Streebog256::digest(b"Hello, World!"));
+* ```
+**/
+#[derive(Clone)]
+pub struct Streebog512 {
+    core: StreebogCore,
+}
+
+#[derive(Clone)]
+pub struct Streebog256 {
+    core: StreebogCore,
+}
+
+///
+/// Функция хэширования с потоковым интерфейсом / Hash function with a streaming interface
+///
+/// Основа для [`Hmac`](crate::Hmac) / Building block for [`Hmac`](crate::Hmac).
+///
+pub trait Digest: Clone + Default {
+    type Output: Copy + AsRef<[u8]> + AsMut<[u8]>;
+    /// Длина хэш-кода в байтах / Digest length in bytes
+    const OUTPUT_SIZE: usize;
+    /// Длина входного блока в байтах / Input block length in bytes
+    const BLOCK_SIZE: usize;
+
+    fn update(&mut self, data: &[u8]);
+    fn finalize(self) -> Self::Output;
+}
+
+impl Streebog512 {
+    pub fn new() -> Self {
+        Streebog512 { core: StreebogCore::new(0x00) }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.core.update(data);
+    }
+
+    pub fn finalize(self) -> Block512 {
+        self.core.finalize()
+    }
+
+    pub fn digest(data: &[u8]) -> Block512 {
+        let mut hasher = Self::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+impl Default for Streebog512 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Digest for Streebog512 {
+    type Output = Block512;
+    const OUTPUT_SIZE: usize = 64;
+    const BLOCK_SIZE: usize = 64;
+
+    fn update(&mut self, data: &[u8]) {
+        Streebog512::update(self, data);
+    }
+
+    fn finalize(self) -> Block512 {
+        Streebog512::finalize(self)
+    }
+}
+
+impl Streebog256 {
+    pub fn new() -> Self {
+        Streebog256 { core: StreebogCore::new(0x01) }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.core.update(data);
+    }
+
+    /// Старшая половина 512-битного результата / The most significant half of the 512-bit result
+    pub fn finalize(self) -> Block256 {
+        let h = self.core.finalize();
+        let mut result: Block256 = [0u8; 32];
+        result.copy_from_slice(&h[32..]);
+        result
+    }
+
+    pub fn digest(data: &[u8]) -> Block256 {
+        let mut hasher = Self::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+impl Default for Streebog256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Digest for Streebog256 {
+    type Output = Block256;
+    const OUTPUT_SIZE: usize = 32;
+    const BLOCK_SIZE: usize = 64;
+
+    fn update(&mut self, data: &[u8]) {
+        Streebog256::update(self, data);
+    }
+
+    fn finalize(self) -> Block256 {
+        Streebog256::finalize(self)
+    }
+}
+
+/// Общее состояние: h, счётчик длины N, контрольная сумма Σ и буфер неполного блока
+#[derive(Clone)]
+struct StreebogCore {
+    h: Block512,
+    n: Block512,
+    sigma: Block512,
+    buffer: Block512,
+    buffer_len: usize,
+}
+
+impl StreebogCore {
+    fn new(iv: u8) -> Self {
+        StreebogCore {
+            h: [iv; 64],
+            n: [0u8; 64],
+            sigma: [0u8; 64],
+            buffer: [0u8; 64],
+            buffer_len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let take = (64 - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+
+            if self.buffer_len == 64 {
+                let block = self.buffer;
+                self.compress(&block, 512);
+                self.buffer_len = 0;
+            }
+        }
+    }
+
+    fn finalize(mut self) -> Block512 {
+        let mut block: Block512 = [0u8; 64];
+        block[..self.buffer_len].copy_from_slice(&self.buffer[..self.buffer_len]);
+        block[self.buffer_len] = 0x01;
+        self.compress(&block, self.buffer_len as u16 * 8);
+
+        let zero = [0u8; 64];
+        let (n, sigma) = (self.n, self.sigma);
+        tfm_g(&mut self.h, &zero, &n);
+        tfm_g(&mut self.h, &zero, &sigma);
+        self.h
+    }
+
+    /// Этап 2 / Stage 2: h = g_N(h, m), N += bits, Σ += m
+    fn compress(&mut self, m: &Block512, bits: u16) {
+        tfm_g(&mut self.h, &self.n, m);
+
+        let mut len: Block512 = [0u8; 64];
+        len[..2].copy_from_slice(&bits.to_le_bytes());
+        add_512(&mut self.n, &len);
+        add_512(&mut self.sigma, m);
+    }
+}
+
+impl Drop for StreebogCore {
+    fn drop(&mut self) {
+        self.h.zeroize();
+        self.n.zeroize();
+        self.sigma.zeroize();
+        self.buffer.zeroize();
+    }
+}
+
+/// Функция сжатия / Compression function g_N(h, m) = E(LPS(h ^ N), m) ^ h ^ m
+fn tfm_g(h: &mut Block512, n: &Block512, m: &Block512) {
+    let mut k = *h;
+    sum_mod_2(&mut k, n);
+    k = tfm_lps(&k);
+
+    let mut state = *m;
+    for c in STREEBOG_C.iter() {
+        sum_mod_2(&mut state, &k);
+        state = tfm_lps(&state);
+
+        sum_mod_2(&mut k, c);
+        k = tfm_lps(&k);
+    }
+    sum_mod_2(&mut state, &k);
+
+    sum_mod_2(h, &state);
+    sum_mod_2(h, m);
+}
+
+/// Композиция подстановки S, перестановки P и линейного преобразования L
+fn tfm_lps(state: &Block512) -> Block512 {
+    let mut result: Block512 = [0u8; 64];
+    for i in 0..8 {
+        let mut word = 0u64;
+        for j in 0..8 {
+            word |= (K_PI[state[8 * j + i] as usize] as u64) << (8 * j);
+        }
+        result[8 * i..8 * (i + 1)].copy_from_slice(&tfm_l(word).to_le_bytes());
+    }
+    result
+}
+
+fn tfm_l(word: u64) -> u64 {
+    let mut result = 0;
+    for (i, row) in STREEBOG_A.iter().enumerate() {
+        if (word >> (63 - i)) & 1 == 1 {
+            result ^= row;
+        }
+    }
+    result
+}
+
+/// Сложение в кольце Z/2^512 / Addition modulo 2^512
+fn add_512(a: &mut Block512, b: &Block512) {
+    let mut carry = 0u16;
+    for (x, y) in a.iter_mut().zip(b.iter()) {
+        let sum = *x as u16 + *y as u16 + carry;
+        *x = sum as u8;
+        carry = sum >> 8;
+    }
+}
@@ -0,0 +1,97 @@
+#![cfg(test)]
+
+use super::{Streebog256, Streebog512};
+
+/// Пример 1 RFC 6986: "012345678901234567890123456789012345678901234567890123456789012"
+const M1: &[u8] = b"012345678901234567890123456789012345678901234567890123456789012";
+
+/// Пример 2 RFC 6986: "Се ветри, Стрибожи внуци, веютъ съ моря стрелами на храбрыя плъкы Игоревы" (cp1251)
+const M2: [u8; 72] = [
+    0xd1, 0xe5, 0x20, 0xe2, 0xe5, 0xf2, 0xf0, 0xe8, 0x2c, 0x20, 0xd1, 0xf2, 0xf0, 0xe8, 0xe1, 0xee,
+    0xe6, 0xe8, 0x20, 0xe2, 0xed, 0xf3, 0xf6, 0xe8, 0x2c, 0x20, 0xe2, 0xe5, 0xfe, 0xf2, 0xfa, 0x20,
+    0xf1, 0x20, 0xec, 0xee, 0xf0, 0xff, 0x20, 0xf1, 0xf2, 0xf0, 0xe5, 0xeb, 0xe0, 0xec, 0xe8, 0x20,
+    0xed, 0xe0, 0x20, 0xf5, 0xf0, 0xe0, 0xe1, 0xf0, 0xfb, 0xff, 0x20, 0xef, 0xeb, 0xfa, 0xea, 0xfb,
+    0x20, 0xc8, 0xe3, 0xee, 0xf0, 0xe5, 0xe2, 0xfb,
+];
+
+#[test]
+fn streebog512_m1() {
+    let expected = [
+        0x1b, 0x54, 0xd0, 0x1a, 0x4a, 0xf5, 0xb9, 0xd5, 0xcc, 0x3d, 0x86, 0xd6, 0x8d, 0x28, 0x54, 0x62,
+        0xb1, 0x9a, 0xbc, 0x24, 0x75, 0x22, 0x2f, 0x35, 0xc0, 0x85, 0x12, 0x2b, 0xe4, 0xba, 0x1f, 0xfa,
+        0x00, 0xad, 0x30, 0xf8, 0x76, 0x7b, 0x3a, 0x82, 0x38, 0x4c, 0x65, 0x74, 0xf0, 0x24, 0xc3, 0x11,
+        0xe2, 0xa4, 0x81, 0x33, 0x2b, 0x08, 0xef, 0x7f, 0x41, 0x79, 0x78, 0x91, 0xc1, 0x64, 0x6f, 0x48,
+    ];
+
+    assert_eq!(Streebog512::digest(M1), expected);
+}
+
+#[test]
+fn streebog256_m1() {
+    let expected = [
+        0x9d, 0x15, 0x1e, 0xef, 0xd8, 0x59, 0x0b, 0x89, 0xda, 0xa6, 0xba, 0x6c, 0xb7, 0x4a, 0xf9, 0x27,
+        0x5d, 0xd0, 0x51, 0x02, 0x6b, 0xb1, 0x49, 0xa4, 0x52, 0xfd, 0x84, 0xe5, 0xe5, 0x7b, 0x55, 0x00,
+    ];
+
+    assert_eq!(Streebog256::digest(M1), expected);
+}
+
+#[test]
+fn streebog512_m2() {
+    let expected = [
+        0x1e, 0x88, 0xe6, 0x22, 0x26, 0xbf, 0xca, 0x6f, 0x99, 0x94, 0xf1, 0xf2, 0xd5, 0x15, 0x69, 0xe0,
+        0xda, 0xf8, 0x47, 0x5a, 0x3b, 0x0f, 0xe6, 0x1a, 0x53, 0x00, 0xee, 0xe4, 0x6d, 0x96, 0x13, 0x76,
+        0x03, 0x5f, 0xe8, 0x35, 0x49, 0xad, 0xa2, 0xb8, 0x62, 0x0f, 0xcd, 0x7c, 0x49, 0x6c, 0xe5, 0xb3,
+        0x3f, 0x0c, 0xb9, 0xdd, 0xdc, 0x2b, 0x64, 0x60, 0x14, 0x3b, 0x03, 0xda, 0xba, 0xc9, 0xfb, 0x28,
+    ];
+
+    assert_eq!(Streebog512::digest(&M2), expected);
+}
+
+#[test]
+fn streebog256_m2() {
+    let expected = [
+        0x9d, 0xd2, 0xfe, 0x4e, 0x90, 0x40, 0x9e, 0x5d, 0xa8, 0x7f, 0x53, 0x97, 0x6d, 0x74, 0x05, 0xb0,
+        0xc0, 0xca, 0xc6, 0x28, 0xfc, 0x66, 0x9a, 0x74, 0x1d, 0x50, 0x06, 0x3c, 0x55, 0x7e, 0x8f, 0x50,
+    ];
+
+    assert_eq!(Streebog256::digest(&M2), expected);
+}
+
+#[test]
+fn streebog_update_in_chunks() {
+    // Assign
+    let data: Vec<u8> = (0..200u8).collect();
+
+    for chunk in [1, 7, 63, 64, 65] {
+        let mut hasher512 = Streebog512::new();
+        let mut hasher256 = Streebog256::new();
+
+        // Act
+        for part in data.chunks(chunk) {
+            hasher512.update(part);
+            hasher256.update(part);
+        }
+
+        // Assert
+        assert_eq!(hasher512.finalize(), Streebog512::digest(&data));
+        assert_eq!(hasher256.finalize(), Streebog256::digest(&data));
+    }
+}
+
+#[test]
+fn streebog_empty() {
+    let expected512 = [
+        0x8e, 0x94, 0x5d, 0xa2, 0x09, 0xaa, 0x86, 0x9f, 0x04, 0x55, 0x92, 0x85, 0x29, 0xbc, 0xae, 0x46,
+        0x79, 0xe9, 0x87, 0x3a, 0xb7, 0x07, 0xb5, 0x53, 0x15, 0xf5, 0x6c, 0xeb, 0x98, 0xbe, 0xf0, 0xa7,
+        0x36, 0x2f, 0x71, 0x55, 0x28, 0x35, 0x6e, 0xe8, 0x3c, 0xda, 0x5f, 0x2a, 0xac, 0x4c, 0x6a, 0xd2,
+        0xba, 0x3a, 0x71, 0x5c, 0x1b, 0xcd, 0x81, 0xcb, 0x8e, 0x9f, 0x90, 0xbf, 0x4c, 0x1c, 0x1a, 0x8a,
+    ];
+    let expected256 = [
+        0x3f, 0x53, 0x9a, 0x21, 0x3e, 0x97, 0xc8, 0x02, 0xcc, 0x22, 0x9d, 0x47, 0x4c, 0x6a, 0xa3, 0x2a,
+        0x82, 0x5a, 0x36, 0x0b, 0x2a, 0x93, 0x3a, 0x94, 0x9f, 0xd9, 0x25, 0x20, 0x8d, 0x9c, 0xe1, 0xbb,
+    ];
+
+    assert_eq!(Streebog512::digest(b""), expected512);
+    assert_eq!(Streebog256::digest(b""), expected256);
+}
@@ -1,9 +1,10 @@
 #![cfg(test)]
 
-use crate::{KeyStore, Kuznechik, AlgOfb};
+use crate::{KeyStore, Kuznechik, AlgOfb, StreamMode};
 
 
 #[test]
+#[cfg(feature = "alloc")]
 fn encrypt_decrypt() {
     // Assign
     let password = "Kuznechik";
@@ -30,3 +31,29 @@ fn encrypt_decrypt() {
     // Assert
     assert_eq!(data, dec_data);
 }
+
+#[test]
+fn encrypt_decrypt_without_alloc() {
+    // Assign
+    let gamma = [
+        0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xce, 0xf0, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf0, 0x01, 0x12,
+        0x23, 0x34, 0x45, 0x56, 0x67, 0x78, 0x89, 0x90, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19,
+    ];
+
+    let kuz = KeyStore::with_password("Kuznechik");
+    let mut cipher = AlgOfb::new(&kuz).try_gamma(&gamma).unwrap();
+
+    let mut buf = [0u8; 32];
+    buf[..13].copy_from_slice(b"Hello, World!");
+
+    // Act
+    cipher.encrypt_in_place(&mut buf[..13]).unwrap();
+    let enc_data = buf;
+
+    cipher.try_set_gamma(&gamma).unwrap();
+    cipher.decrypt_in_place(&mut buf[..13]).unwrap();
+
+    // Assert
+    assert_ne!(&enc_data[..13], b"Hello, World!");
+    assert_eq!(&buf[..13], b"Hello, World!");
+}
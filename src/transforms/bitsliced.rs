@@ -0,0 +1,155 @@
+//! Реализация "Кузнечика" за постоянное время / Constant-time Kuznechik
+//!
+//! Ни одно обращение к памяти не зависит от секретных данных. Подстановка π вычисляется
+//! как булев многочлен (алгебраическая нормальная форма) над битовыми срезами 16 байт блока,
+//! умножения в GF(2^8) преобразования L выполняются сдвигами и масками.
+//! No memory access depends on secret data: π is evaluated as its algebraic normal form over
+//! the bit planes of the 16 block bytes, and the GF(2^8) products of L use shifts and masks.
+
+use core::convert::TryInto;
+use crate::types::{Block128, Block256};
+use crate::tables::{K_PI, K_PI_REV};
+
+/// Коэффициенты АНФ: бит j элемента S - коэффициент монома x^S в j-м выходном бите
+static PI_ANF: [u8; 256] = make_anf(&K_PI);
+static PI_REV_ANF: [u8; 256] = make_anf(&K_PI_REV);
+
+/// Коэффициенты ℓ для байтов 0..15 блока
+const L_COEFFS: [u8; 16] = [148, 32, 133, 16, 194, 192, 1, 251, 1, 192, 194, 16, 133, 32, 148, 1];
+
+pub(crate) fn encrypt_block(data: &mut Block128, keys: &[Block128; 10]) {
+    for key in &keys[..9] {
+        tfm_lsx(data, key);
+    }
+    tfm_x(data, &keys[9]);
+}
+
+pub(crate) fn decrypt_block(data: &mut Block128, keys: &[Block128; 10]) {
+    for i in (1..=9).rev() {
+        tfm_x(data, &keys[i]);
+        tfm_rev_l(data);
+        tfm_sbox(data, &PI_REV_ANF);
+    }
+    tfm_x(data, &keys[0]);
+}
+
+/// Раунд развёртки ключа, аналог `transforms::tfm_f`
+pub(crate) fn tfm_f(data: &mut Block256, key: &Block128) {
+    let temp: Block128 = data[..16].try_into().unwrap();
+
+    let mut left: Block128 = temp;
+    tfm_lsx(&mut left, key);
+    for i in 0..16 {
+        data[i] = left[i] ^ data[i + 16];
+    }
+    data[16..].copy_from_slice(&temp);
+}
+
+fn tfm_lsx(data: &mut Block128, key: &Block128) {
+    tfm_x(data, key);
+    tfm_sbox(data, &PI_ANF);
+    tfm_l(data);
+}
+
+#[cfg(test)]
+pub(crate) fn tfm_s(data: &mut Block128) {
+    tfm_sbox(data, &PI_ANF);
+}
+
+#[cfg(test)]
+pub(crate) fn tfm_rev_s(data: &mut Block128) {
+    tfm_sbox(data, &PI_REV_ANF);
+}
+
+fn tfm_x(data: &mut Block128, key: &Block128) {
+    for i in 0..16 {
+        data[i] ^= key[i];
+    }
+}
+
+/// Подстановка над битовыми срезами: plane[i] содержит i-е биты всех 16 байт
+fn tfm_sbox(data: &mut Block128, anf: &[u8; 256]) {
+    let mut planes = [0u16; 8];
+    for (k, byte) in data.iter().enumerate() {
+        for (i, plane) in planes.iter_mut().enumerate() {
+            *plane |= (((byte >> i) & 1) as u16) << k;
+        }
+    }
+
+    // Мономы x^S = x_{i1} & x_{i2} & ...; monomials[0] - константа 1
+    let mut monomials = [0u16; 256];
+    monomials[0] = 0xffff;
+    for s in 1..256usize {
+        let top = 7 - (s as u8).leading_zeros() as usize;
+        monomials[s] = monomials[s ^ (1 << top)] & planes[top];
+    }
+
+    let mut out = [0u16; 8];
+    for (monomial, coeffs) in monomials.iter().zip(anf.iter()) {
+        for (j, plane) in out.iter_mut().enumerate() {
+            *plane ^= monomial & 0u16.wrapping_sub(((coeffs >> j) & 1) as u16);
+        }
+    }
+
+    for (k, byte) in data.iter_mut().enumerate() {
+        *byte = 0;
+        for (i, plane) in out.iter().enumerate() {
+            *byte |= (((plane >> k) & 1) as u8) << i;
+        }
+    }
+}
+
+pub(crate) fn tfm_l(data: &mut Block128) {
+    for _ in 0..16 {
+        let temp = trf_linear(data);
+        data.rotate_right(1);
+        data[0] = temp;
+    }
+}
+
+pub(crate) fn tfm_rev_l(data: &mut Block128) {
+    for _ in 0..16 {
+        data.rotate_left(1);
+        data[15] = trf_linear(data);
+    }
+}
+
+fn trf_linear(data: &Block128) -> u8 {
+    let mut res = 0u8;
+    for (byte, coeff) in data.iter().zip(L_COEFFS.iter()) {
+        res ^= gf_mul(*byte, *coeff);
+    }
+    res
+}
+
+/// Умножение в GF(2)[x]/p(x), p(x) = x^8 + x^7 + x^6 + x + 1; ветвление только по открытому множителю `c`
+#[inline]
+fn gf_mul(mut a: u8, mut c: u8) -> u8 {
+    let mut res = 0u8;
+    while c != 0 {
+        if c & 1 == 1 {
+            res ^= a;
+        }
+        let mask = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (mask & 0xc3);
+        c >>= 1;
+    }
+    res
+}
+
+/// Преобразование Мёбиуса: таблица истинности подстановки -> коэффициенты АНФ всех 8 выходных битов
+const fn make_anf(sbox: &[u8; 256]) -> [u8; 256] {
+    let mut anf = *sbox;
+    let mut i = 0;
+    while i < 8 {
+        let mut x = 0;
+        while x < 256 {
+            if x & (1 << i) != 0 {
+                anf[x] ^= anf[x ^ (1 << i)];
+            }
+            x += 1;
+        }
+        i += 1;
+    }
+    anf
+}
@@ -0,0 +1,173 @@
+//! Быстрые зашифрование и расшифрование по таблицам / Fast encryption and decryption with precomputed tables
+//!
+//! Преобразования S и L линейно складываются: LS(x) = L(π(x_0), 0, ..., 0) ^ ... ^ L(0, ..., 0, π(x_15)),
+//! поэтому раунд сводится к 16 обращениям к таблицам и 16 сложениям. Таблицы строятся при компиляции из `K_PI` и `MULT_TABLE`.
+//! Since L is linear, LS(x) is the XOR of L applied to each substituted byte in its position,
+//! so a round is 16 lookups and XORs. The tables are built at compile time from `K_PI` and `MULT_TABLE`.
+//!
+//! Для расшифрования L^-1 переносится на раундовые ключи: L^-1(x ^ K) = L^-1(x) ^ L^-1(K),
+//! а S^-1 и L^-1 соседних раундов объединяются в таблицы IL·S^-1.
+//! Decryption moves L^-1 onto the round keys and folds S^-1 and L^-1 of adjacent rounds into one table.
+
+use crate::types::Block128;
+use crate::tables::{K_PI, K_PI_REV, MULT_TABLE};
+use super::tfm_rev_l;
+
+/// LS_TABLE[i][b] = L(π(b) в i-м байте) в виде u128 (байт 0 - младший)
+pub(super) static LS_TABLE: [[u128; 256]; 16] = make_table(&K_PI, false);
+
+/// ILS_TABLE[i][b] = L^-1(π^-1(b) в i-м байте)
+pub(super) static ILS_TABLE: [[u128; 256]; 16] = make_table(&K_PI_REV, true);
+
+pub(crate) fn encrypt_block(data: &mut Block128, keys: &[Block128; 10]) {
+    let mut state = u128::from_le_bytes(*data);
+    for key in &keys[..9] {
+        state ^= u128::from_le_bytes(*key);
+        state = tfm_ls(state);
+    }
+    state ^= u128::from_le_bytes(keys[9]);
+    *data = state.to_le_bytes();
+}
+
+/// `dec_keys` - ключи из [`decryption_keys`] / Round keys prepared by [`decryption_keys`]
+pub(crate) fn decrypt_block(data: &mut Block128, dec_keys: &[Block128; 10]) {
+    // L^-1(y) = L^-1(S^-1(S(y)))
+    let mut bytes = *data;
+    for byte in bytes.iter_mut() {
+        *byte = K_PI[*byte as usize];
+    }
+    let mut state = tfm_ls_table(u128::from_le_bytes(bytes), &ILS_TABLE);
+    state ^= u128::from_le_bytes(dec_keys[9]);
+
+    for key in dec_keys[1..9].iter().rev() {
+        state = tfm_ls_table(state, &ILS_TABLE);
+        state ^= u128::from_le_bytes(*key);
+    }
+
+    let mut bytes = state.to_le_bytes();
+    for byte in bytes.iter_mut() {
+        *byte = K_PI_REV[*byte as usize];
+    }
+    state = u128::from_le_bytes(bytes) ^ u128::from_le_bytes(dec_keys[0]);
+    *data = state.to_le_bytes();
+}
+
+/// Ключи расшифрования: K_1 без изменений, L^-1(K_i) для остальных
+pub(crate) fn decryption_keys(keys: &[Block128; 10]) -> [Block128; 10] {
+    let mut dec_keys = *keys;
+    for key in dec_keys[1..].iter_mut() {
+        tfm_rev_l(key);
+    }
+    dec_keys
+}
+
+#[inline]
+fn tfm_ls(state: u128) -> u128 {
+    tfm_ls_table(state, &LS_TABLE)
+}
+
+#[inline]
+fn tfm_ls_table(state: u128, tables: &[[u128; 256]; 16]) -> u128 {
+    let bytes = state.to_le_bytes();
+    let mut result = 0u128;
+    for (table, byte) in tables.iter().zip(bytes.iter()) {
+        result ^= table[*byte as usize];
+    }
+    result
+}
+
+/// L линейно над GF(2^8): L(π(b) в i-м байте) = π(b) · L(e_i), поэтому достаточно 16 образов единичных векторов
+/// (для таблиц расшифрования - образов L^-1)
+const fn make_table(sbox: &[u8; 256], inverse: bool) -> [[u128; 256]; 16] {
+    let (exp, log) = make_exp_log();
+
+    let mut table = [[0u128; 256]; 16];
+    let mut i = 0;
+    while i < 16 {
+        let mut unit = [0u8; 16];
+        unit[i] = 1;
+        let image = if inverse { const_tfm_rev_l(unit) } else { const_tfm_l(unit) };
+
+        let mut b = 0;
+        while b < 256 {
+            let s = sbox[b];
+            let mut column = [0u8; 16];
+            let mut k = 0;
+            while k < 16 {
+                if s != 0 && image[k] != 0 {
+                    column[k] = exp[(log[s as usize] as usize + log[image[k] as usize] as usize) % 255];
+                }
+                k += 1;
+            }
+            table[i][b] = u128::from_le_bytes(column);
+            b += 1;
+        }
+        i += 1;
+    }
+    table
+}
+
+/// Степени и логарифмы по образующей x поля GF(2)[x]/p(x), p(x) = x^8 + x^7 + x^6 + x + 1
+pub(super) const fn make_exp_log() -> ([u8; 255], [u8; 256]) {
+    let mut exp = [0u8; 255];
+    let mut log = [0u8; 256];
+    let mut x = 1u8;
+    let mut n = 0;
+    while n < 255 {
+        exp[n] = x;
+        log[x as usize] = n as u8;
+        x = (x << 1) ^ if x & 0x80 != 0 { 0xc3 } else { 0 };
+        n += 1;
+    }
+    (exp, log)
+}
+
+/// Преобразование L, как `transforms::tfm_l`, но вычислимое при компиляции
+pub(super) const fn const_tfm_l(mut data: Block128) -> Block128 {
+    let mut round = 0;
+    while round < 16 {
+        let temp = const_linear(&data);
+        let mut k = 15;
+        while k > 0 {
+            data[k] = data[k - 1];
+            k -= 1;
+        }
+        data[0] = temp;
+        round += 1;
+    }
+    data
+}
+
+/// Преобразование L^-1, как `transforms::tfm_rev_l`
+pub(super) const fn const_tfm_rev_l(mut data: Block128) -> Block128 {
+    let mut round = 0;
+    while round < 16 {
+        let first = data[0];
+        let mut k = 0;
+        while k < 15 {
+            data[k] = data[k + 1];
+            k += 1;
+        }
+        data[15] = first;
+        data[15] = const_linear(&data);
+        round += 1;
+    }
+    data
+}
+
+/// Линейное преобразование ℓ, как `transforms::trf_linear`
+const fn const_linear(data: &Block128) -> u8 {
+    // Номера строк MULT_TABLE для коэффициентов ℓ, 7 - умножение на 1
+    const ROWS: [usize; 16] = [3, 1, 2, 0, 5, 4, 7, 6, 7, 4, 5, 0, 2, 1, 3, 7];
+
+    let mut res = 0u8;
+    let mut j = 0;
+    while j < 16 {
+        res ^= match ROWS[j] {
+            7 => data[j],
+            row => MULT_TABLE[row][data[j] as usize],
+        };
+        j += 1;
+    }
+    res
+}
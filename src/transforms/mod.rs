@@ -1,13 +1,19 @@
 mod tests;
+pub(crate) mod bitsliced;
+pub(crate) mod ls_tables;
+pub(crate) mod simd;
 
-use std::convert::TryInto;
+use core::convert::TryInto;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use crate::types::{Block128, Block256, mut_cast_unchecked};
 use crate::tables::{K_PI, MULT_TABLE, K_PI_REV};
+use crate::error::KuznechikError;
 
 #[inline]
 pub(crate) fn encrypt_block(data: &mut Block128, keys: &[Block128; 10]) {
-    for i in 0..9 {
-        tfm_lsx(data, &keys[i]);
+    for key in &keys[..9] {
+        tfm_lsx(data, key);
     }
     tfm_x(data, &keys[9]);
 }
@@ -35,8 +41,8 @@ pub(crate) fn tfm_c(data: &mut Block128, number: u8) {
 pub(crate) fn tfm_f(data: &mut Block256, key: &Block128) {
     let temp: Block128 = data[..16].try_into().unwrap();
 
-    let mut data_left = mut_cast_unchecked(&mut data[..16]);
-    tfm_lsx(&mut data_left, &key);
+    let data_left = mut_cast_unchecked(&mut data[..16]);
+    tfm_lsx(data_left, key);
     tfm_x_block256(data);
     data[16..].copy_from_slice(&temp);
 }
@@ -132,7 +138,7 @@ fn tfm_rev_r(data: &mut Block128) {
 
 /// Обратное преобразование L
 #[inline]
-fn tfm_rev_l(data: &mut Block128) {
+pub(crate) fn tfm_rev_l(data: &mut Block128) {
     for _ in 0..16 {
         tfm_rev_r(data);
     }
@@ -149,31 +155,71 @@ pub(crate) fn sum_mod_2(b1: &mut [u8], b2: &[u8]) {
     }
 }
 
-#[inline]
-pub(crate) fn addition_block128_2(data: &mut Vec<u8>) {
-    addition_block_s_2(data, 16);
-}
-
+/// Процедура дополнения 2: к данным дописывается единичный бит и нули
+/// до длины, кратной s. Если длина уже кратна s, дописывается целый блок.
+#[cfg(feature = "alloc")]
 #[inline]
 pub(crate) fn addition_block_s_2(data: &mut Vec<u8>, s: usize) {
     let len = data.len();
-    let r = match len % s {
-        0 => s,
-        r => r
-    };
+    let r = s - len % s;
 
     data.resize(len + r, 0);
     data[len] = 0x80;
 }
 
+/// Снятие дополнения, выполненного процедурой 2.
+/// Дополнение должно целиком находиться в последних s байтах.
+#[cfg(feature = "alloc")]
 #[inline]
-pub(crate) fn addition_rev_block_2(data: &mut Vec<u8>) {
-    let new_len = {
-        match data.iter().rposition(|&x| x == 0x80) {
-            Some(l) => l,
-            None => data.len()
-        }
-    };
+pub(crate) fn addition_rev_block_2(data: &mut Vec<u8>, s: usize) -> Result<(), KuznechikError> {
+    let new_len = padding_start(data, s)?;
+    data.truncate(new_len);
+    Ok(())
+}
 
-    data.resize(new_len, 0);
+/// Процедура дополнения 2 на месте: данные занимают первые `len` байт буфера,
+/// возвращается длина дополненных данных
+#[inline]
+pub(crate) fn addition_block_2_in_place(buf: &mut [u8], len: usize, s: usize) -> Result<usize, KuznechikError> {
+    let padded_len = len - len % s + s;
+    if buf.len() < padded_len {
+        return Err(KuznechikError::BufferTooSmall);
+    }
+
+    buf[len] = 0x80;
+    buf[len + 1..padded_len].fill(0);
+    Ok(padded_len)
+}
+
+/// Длина данных без дополнения процедуры 2
+#[inline]
+pub(crate) fn padding_start(data: &[u8], s: usize) -> Result<usize, KuznechikError> {
+    let tail_start = data.len().saturating_sub(s);
+    match data[tail_start..].iter().rposition(|&x| x != 0) {
+        Some(l) if data[tail_start + l] == 0x80 => Ok(tail_start + l),
+        _ => Err(KuznechikError::InvalidPadding)
+    }
+}
+
+/// Проверка длины шифртекста: он должен быть непустым и кратным s
+#[inline]
+pub(crate) fn check_data_len(data: &[u8], s: usize) -> Result<(), KuznechikError> {
+    if data.is_empty() || !data.len().is_multiple_of(s) {
+        return Err(KuznechikError::InvalidDataLength);
+    }
+    Ok(())
+}
+
+/// Сравнение за постоянное время (не зависит от позиции первого различия)
+#[inline]
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut diff = 0u8;
+    for i in 0..a.len() {
+        diff |= a[i] ^ b[i];
+    }
+    core::hint::black_box(diff) == 0
 }
@@ -0,0 +1,412 @@
+//! Векторная реализация на SSSE3/AVX2 / Vectorized implementation with SSSE3/AVX2
+//!
+//! Несколько блоков обрабатываются одновременно в побайтно-срезанном виде: j-й регистр содержит j-е байты
+//! 16 (SSSE3) или 32 (AVX2) блоков. Подстановка π выполняется командой `pshufb` по полубайтам: младший полубайт
+//! выбирает элемент строки таблицы, старший - строку по маске сравнения. L задаётся матрицей 16x16 над GF(2^8),
+//! умножение регистра на её элемент - две выборки `pshufb` по младшему и старшему полубайтам.
+//! Обращений к памяти по секретным индексам нет.
+//! Several blocks are processed at once in byte-sliced form: register j holds byte j of every block.
+//! π is a nibble-wise `pshufb` lookup, and L is a 16x16 matrix over GF(2^8) applied with `pshufb` product tables.
+//! There is no secret-dependent memory access in this path.
+//!
+//! Одиночные блоки и остаток, меньший ширины регистра, обрабатываются по таблицам LS со сложением в регистрах SSE2.
+//! Single blocks and the tail go through the LS tables with SSE2 XORs.
+//! Набор команд определяется во время выполнения (без `std` - при компиляции), без x86-64 используется переносимая реализация по таблицам.
+//! The instruction set is detected at run time (at compile time without `std`); other targets fall back to the portable table code.
+
+use crate::types::Block128;
+
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+#[cfg(target_arch = "x86_64")]
+use crate::tables::{K_PI, K_PI_REV};
+#[cfg(target_arch = "x86_64")]
+use super::ls_tables::{LS_TABLE, ILS_TABLE, make_exp_log, const_tfm_l, const_tfm_rev_l};
+
+#[cfg(not(target_arch = "x86_64"))]
+use super::ls_tables;
+
+/// Таблицы умножения на элементы матрицы L: [k][j] = (M[k][j] · n, M[k][j] · (n << 4)), n = 0..15
+#[cfg(target_arch = "x86_64")]
+type MulTables = [[[[u8; 16]; 2]; 16]; 16];
+
+#[cfg(target_arch = "x86_64")]
+static L_MUL: MulTables = make_mul_tables(false);
+
+#[cfg(target_arch = "x86_64")]
+static L_REV_MUL: MulTables = make_mul_tables(true);
+
+/// Строки подстановки по старшему полубайту: [h][l] = π(16h + l)
+#[cfg(target_arch = "x86_64")]
+static PI_ROWS: [[u8; 16]; 16] = make_rows(&K_PI);
+
+#[cfg(target_arch = "x86_64")]
+static PI_REV_ROWS: [[u8; 16]; 16] = make_rows(&K_PI_REV);
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn encrypt_block(data: &mut Block128, keys: &[Block128; 10]) {
+    // SSE2 входит в базовый набор x86-64
+    unsafe { sse2::encrypt_block(data, keys) }
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn decrypt_block(data: &mut Block128, dec_keys: &[Block128; 10]) {
+    unsafe { sse2::decrypt_block(data, dec_keys) }
+}
+
+/// Зашифрование подряд идущих блоков, `data.len()` кратна 16
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn encrypt_blocks(data: &mut [u8], keys: &[Block128; 10]) {
+    let mut rest = data;
+    if has_avx2() {
+        let mut chunks = rest.chunks_exact_mut(avx2::LANES * 16);
+        for chunk in &mut chunks {
+            unsafe { avx2::encrypt(chunk, keys) }
+        }
+        rest = chunks.into_remainder();
+    }
+    if has_ssse3() {
+        let mut chunks = rest.chunks_exact_mut(ssse3::LANES * 16);
+        for chunk in &mut chunks {
+            unsafe { ssse3::encrypt(chunk, keys) }
+        }
+        rest = chunks.into_remainder();
+    }
+    for chunk in rest.chunks_exact_mut(16) {
+        let block: &mut Block128 = chunk.try_into().unwrap();
+        encrypt_block(block, keys);
+    }
+}
+
+/// Расшифрование подряд идущих блоков: векторный путь использует `keys`, одиночные блоки - `dec_keys`
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn decrypt_blocks(data: &mut [u8], keys: &[Block128; 10], dec_keys: &[Block128; 10]) {
+    let mut rest = data;
+    if has_avx2() {
+        let mut chunks = rest.chunks_exact_mut(avx2::LANES * 16);
+        for chunk in &mut chunks {
+            unsafe { avx2::decrypt(chunk, keys) }
+        }
+        rest = chunks.into_remainder();
+    }
+    if has_ssse3() {
+        let mut chunks = rest.chunks_exact_mut(ssse3::LANES * 16);
+        for chunk in &mut chunks {
+            unsafe { ssse3::decrypt(chunk, keys) }
+        }
+        rest = chunks.into_remainder();
+    }
+    for chunk in rest.chunks_exact_mut(16) {
+        let block: &mut Block128 = chunk.try_into().unwrap();
+        decrypt_block(block, dec_keys);
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub(crate) fn encrypt_block(data: &mut Block128, keys: &[Block128; 10]) {
+    ls_tables::encrypt_block(data, keys)
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub(crate) fn decrypt_block(data: &mut Block128, dec_keys: &[Block128; 10]) {
+    ls_tables::decrypt_block(data, dec_keys)
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub(crate) fn encrypt_blocks(data: &mut [u8], keys: &[Block128; 10]) {
+    for chunk in data.chunks_exact_mut(16) {
+        let block: &mut Block128 = chunk.try_into().unwrap();
+        ls_tables::encrypt_block(block, keys);
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub(crate) fn decrypt_blocks(data: &mut [u8], _keys: &[Block128; 10], dec_keys: &[Block128; 10]) {
+    for chunk in data.chunks_exact_mut(16) {
+        let block: &mut Block128 = chunk.try_into().unwrap();
+        ls_tables::decrypt_block(block, dec_keys);
+    }
+}
+
+/// Без `std` набор команд берётся из настроек компиляции (`-C target-feature`)
+#[cfg(all(target_arch = "x86_64", feature = "std"))]
+fn has_avx2() -> bool {
+    is_x86_feature_detected!("avx2")
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "std"))]
+fn has_ssse3() -> bool {
+    is_x86_feature_detected!("ssse3")
+}
+
+#[cfg(all(target_arch = "x86_64", not(feature = "std")))]
+fn has_avx2() -> bool {
+    cfg!(target_feature = "avx2")
+}
+
+#[cfg(all(target_arch = "x86_64", not(feature = "std")))]
+fn has_ssse3() -> bool {
+    cfg!(target_feature = "ssse3")
+}
+
+/// Один блок по таблицам LS, сложение строк таблиц в регистрах SSE2
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+    use super::*;
+
+    pub(super) unsafe fn encrypt_block(data: &mut Block128, keys: &[Block128; 10]) {
+        let mut state = load(data);
+        for key in &keys[..9] {
+            state = _mm_xor_si128(state, load(key));
+            state = tfm_ls(state, &LS_TABLE);
+        }
+        state = _mm_xor_si128(state, load(&keys[9]));
+        store(state, data);
+    }
+
+    /// Порядок преобразований как в `ls_tables::decrypt_block`
+    pub(super) unsafe fn decrypt_block(data: &mut Block128, dec_keys: &[Block128; 10]) {
+        let mut bytes = *data;
+        for byte in bytes.iter_mut() {
+            *byte = K_PI[*byte as usize];
+        }
+        let mut state = tfm_ls(load(&bytes), &ILS_TABLE);
+        state = _mm_xor_si128(state, load(&dec_keys[9]));
+
+        for key in dec_keys[1..9].iter().rev() {
+            state = tfm_ls(state, &ILS_TABLE);
+            state = _mm_xor_si128(state, load(key));
+        }
+
+        store(state, &mut bytes);
+        for byte in bytes.iter_mut() {
+            *byte = K_PI_REV[*byte as usize];
+        }
+        state = _mm_xor_si128(load(&bytes), load(&dec_keys[0]));
+        store(state, data);
+    }
+
+    #[inline]
+    unsafe fn tfm_ls(state: __m128i, tables: &[[u128; 256]; 16]) -> __m128i {
+        let mut bytes = [0u8; 16];
+        store(state, &mut bytes);
+
+        let mut result = _mm_setzero_si128();
+        for (table, byte) in tables.iter().zip(bytes.iter()) {
+            let row = &table[*byte as usize] as *const u128 as *const __m128i;
+            result = _mm_xor_si128(result, _mm_loadu_si128(row));
+        }
+        result
+    }
+
+    #[inline]
+    unsafe fn load(block: &Block128) -> __m128i {
+        _mm_loadu_si128(block.as_ptr() as *const __m128i)
+    }
+
+    #[inline]
+    unsafe fn store(state: __m128i, block: &mut Block128) {
+        _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, state)
+    }
+}
+
+/// Побайтно-срезанная реализация для регистра из `$lanes` байт; различаются только команды
+#[cfg(target_arch = "x86_64")]
+macro_rules! byte_sliced {
+    ($name:ident, $feature:literal, $vec:ty, $lanes:expr,
+     $load:ident, $store:ident, $table:ident, $set1:ident, $zero:ident,
+     $xor:ident, $and:ident, $or:ident, $shuffle:ident, $cmpeq:ident, $srli:ident) => {
+        mod $name {
+            use super::*;
+
+            /// Число блоков, обрабатываемых за раз / Blocks per call
+            pub(super) const LANES: usize = $lanes;
+
+            /// `data` - ровно `LANES` блоков
+            #[target_feature(enable = $feature)]
+            pub(super) unsafe fn encrypt(data: &mut [u8], keys: &[Block128; 10]) {
+                let mut state = load_state(data);
+                for key in &keys[..9] {
+                    add_key(&mut state, key);
+                    tfm_s(&mut state, &PI_ROWS);
+                    state = tfm_l(&state, &L_MUL);
+                }
+                add_key(&mut state, &keys[9]);
+                store_state(&state, data);
+            }
+
+            #[target_feature(enable = $feature)]
+            pub(super) unsafe fn decrypt(data: &mut [u8], keys: &[Block128; 10]) {
+                let mut state = load_state(data);
+                for key in keys[1..].iter().rev() {
+                    add_key(&mut state, key);
+                    state = tfm_l(&state, &L_REV_MUL);
+                    tfm_s(&mut state, &PI_REV_ROWS);
+                }
+                add_key(&mut state, &keys[0]);
+                store_state(&state, data);
+            }
+
+            /// Транспонирование: j-й регистр собирает j-е байты всех блоков
+            #[target_feature(enable = $feature)]
+            #[inline]
+            unsafe fn load_state(data: &[u8]) -> [$vec; 16] {
+                let mut state = [$zero(); 16];
+                let mut column = [0u8; LANES];
+                for (j, reg) in state.iter_mut().enumerate() {
+                    for (b, byte) in column.iter_mut().enumerate() {
+                        *byte = data[b * 16 + j];
+                    }
+                    *reg = $load(&column);
+                }
+                state
+            }
+
+            #[target_feature(enable = $feature)]
+            #[inline]
+            unsafe fn store_state(state: &[$vec; 16], data: &mut [u8]) {
+                let mut column = [0u8; LANES];
+                for (j, reg) in state.iter().enumerate() {
+                    $store(*reg, &mut column);
+                    for (b, byte) in column.iter().enumerate() {
+                        data[b * 16 + j] = *byte;
+                    }
+                }
+            }
+
+            #[target_feature(enable = $feature)]
+            #[inline]
+            unsafe fn add_key(state: &mut [$vec; 16], key: &Block128) {
+                for (reg, byte) in state.iter_mut().zip(key.iter()) {
+                    *reg = $xor(*reg, $set1(*byte as i8));
+                }
+            }
+
+            /// Подстановка: выборка по младшему полубайту из каждой из 16 строк, строка выбирается маской
+            #[target_feature(enable = $feature)]
+            #[inline]
+            unsafe fn tfm_s(state: &mut [$vec; 16], rows: &[[u8; 16]; 16]) {
+                let mask = $set1(0x0f);
+                for reg in state.iter_mut() {
+                    let lo = $and(*reg, mask);
+                    let hi = $and($srli::<4>(*reg), mask);
+
+                    let mut result = $zero();
+                    for (h, row) in rows.iter().enumerate() {
+                        let value = $shuffle($table(row), lo);
+                        let select = $cmpeq(hi, $set1(h as i8));
+                        result = $or(result, $and(value, select));
+                    }
+                    *reg = result;
+                }
+            }
+
+            /// out_k = Σ_j M[k][j] · x_j, умножение по полубайтам
+            #[target_feature(enable = $feature)]
+            #[inline]
+            unsafe fn tfm_l(state: &[$vec; 16], mul: &MulTables) -> [$vec; 16] {
+                let mask = $set1(0x0f);
+                let mut lo = [$zero(); 16];
+                let mut hi = [$zero(); 16];
+                for j in 0..16 {
+                    lo[j] = $and(state[j], mask);
+                    hi[j] = $and($srli::<4>(state[j]), mask);
+                }
+
+                let mut result = [$zero(); 16];
+                for (out, row) in result.iter_mut().zip(mul.iter()) {
+                    for j in 0..16 {
+                        *out = $xor(*out, $shuffle($table(&row[j][0]), lo[j]));
+                        *out = $xor(*out, $shuffle($table(&row[j][1]), hi[j]));
+                    }
+                }
+                result
+            }
+        }
+    };
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+#[inline]
+unsafe fn load_128(column: &[u8; 16]) -> __m128i {
+    _mm_loadu_si128(column.as_ptr() as *const __m128i)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+#[inline]
+unsafe fn store_128(reg: __m128i, column: &mut [u8; 16]) {
+    _mm_storeu_si128(column.as_mut_ptr() as *mut __m128i, reg)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+#[inline]
+unsafe fn load_256(column: &[u8; 32]) -> __m256i {
+    _mm256_loadu_si256(column.as_ptr() as *const __m256i)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+#[inline]
+unsafe fn store_256(reg: __m256i, column: &mut [u8; 32]) {
+    _mm256_storeu_si256(column.as_mut_ptr() as *mut __m256i, reg)
+}
+
+/// `vpshufb` выбирает внутри 128-битных половин, поэтому таблица дублируется в обе
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+#[inline]
+unsafe fn table_256(table: &[u8; 16]) -> __m256i {
+    _mm256_broadcastsi128_si256(load_128(table))
+}
+
+#[cfg(target_arch = "x86_64")]
+byte_sliced!(ssse3, "ssse3", __m128i, 16,
+    load_128, store_128, load_128, _mm_set1_epi8, _mm_setzero_si128,
+    _mm_xor_si128, _mm_and_si128, _mm_or_si128, _mm_shuffle_epi8, _mm_cmpeq_epi8, _mm_srli_epi16);
+
+#[cfg(target_arch = "x86_64")]
+byte_sliced!(avx2, "avx2", __m256i, 32,
+    load_256, store_256, table_256, _mm256_set1_epi8, _mm256_setzero_si256,
+    _mm256_xor_si256, _mm256_and_si256, _mm256_or_si256, _mm256_shuffle_epi8, _mm256_cmpeq_epi8, _mm256_srli_epi16);
+
+#[cfg(target_arch = "x86_64")]
+const fn make_rows(sbox: &[u8; 256]) -> [[u8; 16]; 16] {
+    let mut rows = [[0u8; 16]; 16];
+    let mut i = 0;
+    while i < 256 {
+        rows[i >> 4][i & 0x0f] = sbox[i];
+        i += 1;
+    }
+    rows
+}
+
+/// M[k][j] = L(e_j)[k] (для расшифрования - L^-1)
+#[cfg(target_arch = "x86_64")]
+const fn make_mul_tables(inverse: bool) -> MulTables {
+    let (exp, log) = make_exp_log();
+
+    let mut tables = [[[[0u8; 16]; 2]; 16]; 16];
+    let mut j = 0;
+    while j < 16 {
+        let mut unit = [0u8; 16];
+        unit[j] = 1;
+        let image = if inverse { const_tfm_rev_l(unit) } else { const_tfm_l(unit) };
+
+        let mut k = 0;
+        while k < 16 {
+            let c = image[k];
+            let mut n = 1;
+            while n < 16 && c != 0 {
+                tables[k][j][0][n] = exp[(log[n] as usize + log[c as usize] as usize) % 255];
+                tables[k][j][1][n] = exp[(log[n << 4] as usize + log[c as usize] as usize) % 255];
+                n += 1;
+            }
+            k += 1;
+        }
+        j += 1;
+    }
+    tables
+}
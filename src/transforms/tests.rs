@@ -1,7 +1,7 @@
 #![cfg(test)]
 
-use crate::KeyStore;
-use super::{decrypt_block, encrypt_block, tfm_l, tfm_r, tfm_s};
+use crate::{KeyStore, Backend};
+use super::{decrypt_block, encrypt_block, tfm_l, tfm_r, tfm_s, tfm_rev_l, tfm_rev_s, bitsliced, ls_tables, simd};
 
 
 #[test]
@@ -12,7 +12,7 @@ fn encrypt_decrypt_block() {
     let kuz = KeyStore::with_master_key(master_key);
 
     let mut data = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x00, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88];
-    let data_backup = data.clone();
+    let data_backup = data;
 
     encrypt_block(&mut data, &kuz.keys);
     assert_eq!(&data, &[0x7f, 0x67, 0x9d, 0x90, 0xbe, 0xbc, 0x24, 0x30, 0x5a, 0x46, 0x8d, 0x42, 0xb9, 0xd4, 0xed, 0xcd]);
@@ -71,3 +71,140 @@ fn test_transform_l() {
     tfm_l(&mut data);
     assert_eq!(data, [0xe6, 0xa8, 0x09, 0x4f, 0xee, 0x0a, 0xa2, 0x04, 0xfd, 0x97, 0xbc, 0xb0, 0xb4, 0x4b, 0x85, 0x80]);
 }
+
+#[test]
+fn bitsliced_encrypt_decrypt_block() {
+    let master_key = [0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+        0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+
+    let kuz = KeyStore::with_master_key(master_key);
+
+    let mut data = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x00, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88];
+    let data_backup = data;
+
+    bitsliced::encrypt_block(&mut data, &kuz.keys);
+    assert_eq!(&data, &[0x7f, 0x67, 0x9d, 0x90, 0xbe, 0xbc, 0x24, 0x30, 0x5a, 0x46, 0x8d, 0x42, 0xb9, 0xd4, 0xed, 0xcd]);
+
+    bitsliced::decrypt_block(&mut data, &kuz.keys);
+    assert_eq!(data, data_backup);
+}
+
+#[test]
+fn bitsliced_matches_table() {
+    // S и S^-1 на всех 256 значениях байта
+    for start in (0..256).step_by(16) {
+        let block: [u8; 16] = std::array::from_fn(|i| (start + i) as u8);
+        let (mut table, mut ct) = (block, block);
+
+        tfm_s(&mut table);
+        bitsliced::tfm_s(&mut ct);
+        assert_eq!(table, ct);
+
+        tfm_rev_s(&mut table);
+        bitsliced::tfm_rev_s(&mut ct);
+        assert_eq!(table, block);
+        assert_eq!(ct, block);
+    }
+
+    // L и L^-1, блоки шифрования и развёртка ключа
+    let mut block: [u8; 16] = std::array::from_fn(|i| (i as u8).wrapping_mul(37) ^ 0x5a);
+    for round in 0..64u8 {
+        let key = KeyStore::with_master_key([round; 32]);
+        let ct_key = KeyStore::with_backend(Backend::ConstantTime).master_key([round; 32]);
+        assert!(key.keys == ct_key.keys);
+
+        let (mut table, mut ct) = (block, block);
+        tfm_l(&mut table);
+        bitsliced::tfm_l(&mut ct);
+        assert_eq!(table, ct);
+
+        tfm_rev_l(&mut table);
+        bitsliced::tfm_rev_l(&mut ct);
+        assert_eq!(table, ct);
+
+        encrypt_block(&mut table, &key.keys);
+        bitsliced::encrypt_block(&mut ct, &key.keys);
+        assert_eq!(table, ct);
+
+        decrypt_block(&mut table, &key.keys);
+        bitsliced::decrypt_block(&mut ct, &key.keys);
+        assert_eq!(table, block);
+        assert_eq!(ct, block);
+
+        block[round as usize % 16] ^= round.wrapping_mul(0x9d);
+    }
+}
+
+#[test]
+fn ls_tables_match_reference() {
+    let master_key = [0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+        0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+
+    let kuz = KeyStore::with_master_key(master_key);
+
+    let mut data = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x00, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88];
+    let data_backup = data;
+
+    ls_tables::encrypt_block(&mut data, &kuz.keys);
+    assert_eq!(&data, &[0x7f, 0x67, 0x9d, 0x90, 0xbe, 0xbc, 0x24, 0x30, 0x5a, 0x46, 0x8d, 0x42, 0xb9, 0xd4, 0xed, 0xcd]);
+
+    ls_tables::decrypt_block(&mut data, &ls_tables::decryption_keys(&kuz.keys));
+    assert_eq!(data, data_backup);
+
+    let mut block: [u8; 16] = std::array::from_fn(|i| (i as u8).wrapping_mul(73) ^ 0xa5);
+    for round in 0..256usize {
+        let key = KeyStore::with_master_key([round as u8; 32]);
+        let (mut reference, mut fast) = (block, block);
+
+        encrypt_block(&mut reference, &key.keys);
+        ls_tables::encrypt_block(&mut fast, &key.keys);
+        assert_eq!(reference, fast);
+
+        let (mut reference_dec, mut fast_dec) = (block, block);
+        decrypt_block(&mut reference_dec, &key.keys);
+        ls_tables::decrypt_block(&mut fast_dec, &ls_tables::decryption_keys(&key.keys));
+        assert_eq!(reference_dec, fast_dec);
+
+        block[round % 16] = block[round % 16].wrapping_add(round as u8) ^ fast[0];
+    }
+}
+
+#[test]
+fn simd_matches_scalar() {
+    // Assign
+    // 32 + 16 + 3 блока: проходят векторы AVX2, SSSE3 и одиночные блоки
+    let data: Vec<u8> = (0..51 * 16).map(|i| (i as u8).wrapping_mul(29) ^ (i >> 8) as u8).collect();
+
+    for round in 0..16u8 {
+        let key = KeyStore::with_master_key([round.wrapping_mul(17); 32]);
+
+        for count in [0, 1, 15, 16, 17, 32, 33, 48, 51] {
+            let data = &data[..count * 16];
+
+            // Act
+            let mut scalar = data.to_vec();
+            for chunk in scalar.chunks_exact_mut(16) {
+                encrypt_block(chunk.try_into().unwrap(), &key.keys);
+            }
+
+            let mut vector = data.to_vec();
+            simd::encrypt_blocks(&mut vector, &key.keys);
+
+            // Assert
+            assert_eq!(vector, scalar);
+
+            simd::decrypt_blocks(&mut vector, &key.keys, &ls_tables::decryption_keys(&key.keys));
+            assert_eq!(vector, data);
+        }
+
+        let mut block: [u8; 16] = data[round as usize * 16..][..16].try_into().unwrap();
+        let mut reference = block;
+        simd::encrypt_block(&mut block, &key.keys);
+        encrypt_block(&mut reference, &key.keys);
+        assert_eq!(block, reference);
+
+        simd::decrypt_block(&mut block, &ls_tables::decryption_keys(&key.keys));
+        decrypt_block(&mut reference, &key.keys);
+        assert_eq!(block, reference);
+    }
+}
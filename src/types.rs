@@ -1,5 +1,7 @@
+pub type Block64 = [u8; 8];
 pub type Block128 = [u8; 16];
 pub type Block256 = [u8; 32];
+pub type Block512 = [u8; 64];
 
 pub trait Array: Sized {
     type Item;